@@ -0,0 +1,60 @@
+//! Compares flushing `FileStream` after every log with the log file kept open
+//! against reopening it for every flush.
+use std::hint::black_box;
+
+use criterion::{
+    Criterion,
+    Throughput,
+    criterion_group,
+    criterion_main
+};
+
+use prettylogger::{
+    config::LogStruct,
+    format::LogFormatter,
+    output::FileStream,
+};
+
+fn file_stream(name: &str) -> FileStream {
+    let mut path = std::env::temp_dir();
+    path.push("libprettylogger-benches");
+    std::fs::create_dir_all(&path).expect("Failed to create a directory!");
+    path.push(name);
+
+    let mut file_output = FileStream::default();
+    file_output.set_log_file_path(path.to_str().unwrap())
+        .expect("Failed to set the log file path!");
+    file_output.enable().expect("Failed to enable the output!");
+    file_output.set_max_buffer_size(Some(1));
+    file_output
+}
+
+fn bench_file_output(c: &mut Criterion) {
+    let log = LogStruct::info("Hello from the benchmark!");
+    let mut formatter = LogFormatter::default();
+
+    let mut group = c.benchmark_group("file_output");
+    group.throughput(Throughput::Elements(1));
+
+    let mut file_output = file_stream("persistent_handle.log");
+    group.bench_function("persistent_handle", |b| {
+        b.iter(|| {
+            file_output.out(black_box(&log), &mut formatter)
+                .expect("Failed to write the log!");
+        })
+    });
+
+    let mut file_output = file_stream("reopen_per_flush.log");
+    group.bench_function("reopen_per_flush", |b| {
+        b.iter(|| {
+            file_output.reopen().expect("Failed to reopen the log file!");
+            file_output.out(black_box(&log), &mut formatter)
+                .expect("Failed to write the log!");
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_file_output);
+criterion_main!(benches);
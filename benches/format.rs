@@ -0,0 +1,86 @@
+//! Compares the throughput of `LogFormatter::format_log` against the
+//! character-by-character formatting it replaced.
+use std::hint::black_box;
+
+use criterion::{
+    Criterion,
+    Throughput,
+    criterion_group,
+    criterion_main
+};
+
+use prettylogger::{
+    colors::{
+        Color,
+        color_text
+    },
+    config::LogStruct,
+    format::LogFormatter,
+};
+
+const LOG_FORMAT: &str = "[%h] <%d> %m";
+const DATETIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// The pre-compilation implementation of `format_log`, which rescanned the
+/// log format for every log.
+fn format_log_uncompiled(log: &LogStruct) -> String {
+    let header = color_text("DBG", Color::Blue);
+    let datetime = log.datetime.format(DATETIME_FORMAT).to_string();
+    let mut result = String::new();
+    let mut char_iter = LOG_FORMAT.char_indices().peekable();
+
+    while let Some((_, c)) = char_iter.next() {
+        match c {
+            '%' => {
+                if let Some((_, nc)) = char_iter.peek() {
+                    match nc {
+                        'h' => result += &header,
+                        'd' => result += &datetime,
+                        'm' => result += &log.message,
+                        _ => result += &nc.to_string(),
+                    }
+                    char_iter.next();
+                }
+            }
+            _ => {
+                result += &c.to_string();
+            }
+        }
+    }
+
+    result += "\n";
+    result
+}
+
+fn format_log(c: &mut Criterion) {
+    let log = LogStruct::debug("The quick brown fox jumps over the lazy dog");
+    let mut formatter = LogFormatter::default();
+    formatter.set_log_format(LOG_FORMAT)
+        .expect("Failed to set log format!");
+    formatter.set_datetime_format(DATETIME_FORMAT)
+        .expect("Failed to set datetime format!");
+
+    let mut group = c.benchmark_group("format_log");
+    group.throughput(Throughput::Elements(1));
+
+    group.bench_function("uncompiled", |b| {
+        b.iter(|| format_log_uncompiled(black_box(&log)))
+    });
+
+    group.bench_function("compiled", |b| {
+        b.iter(|| formatter.format_log(black_box(&log)))
+    });
+
+    let mut buf = String::new();
+    group.bench_function("compiled_into_buffer", |b| {
+        b.iter(|| {
+            buf.clear();
+            formatter.format_log_into(black_box(&log), &mut buf);
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, format_log);
+criterion_main!(benches);
@@ -0,0 +1,111 @@
+//! Provides clocks used by `Logger` to timestamp logs.
+
+/// Provides clocks used by `Logger` to timestamp logs.
+use std::{
+    fmt::Debug,
+    sync::Mutex,
+};
+
+use chrono::{
+    DateTime,
+    Local,
+    TimeDelta
+};
+
+/// A source of the current date and time.
+///
+/// `Logger` uses a `Clock` to timestamp every log it creates. Replacing the
+/// default `SystemClock` with a `ManualClock` makes the timestamps (and
+/// therefore formatted logs) deterministic, which is useful in tests.
+///
+/// # Examples
+///
+/// Implementing a custom clock:
+/// ```
+/// # use prettylogger::clock::Clock;
+/// # use chrono::{DateTime, Local, TimeZone};
+/// /// A clock that is stuck at the Unix epoch
+/// #[derive(Debug)]
+/// struct EpochClock;
+///
+/// impl Clock for EpochClock {
+///     fn now(&self) -> DateTime<Local> {
+///         Local.timestamp_opt(0, 0).unwrap()
+///     }
+/// }
+/// ```
+pub trait Clock: Debug + Send + Sync {
+    /// Returns the current date and time.
+    fn now(&self) -> DateTime<Local>;
+}
+
+/// A `Clock` reading the system time. Used by `Logger` by default.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default)]
+pub struct SystemClock;
+
+/// A `Clock` that only moves when told to.
+///
+/// # Examples
+///
+/// Pinning the timestamps of a `Logger`:
+/// ```
+/// # use std::sync::Arc;
+/// # use prettylogger::{
+/// #     Logger,
+/// #     clock::ManualClock,
+/// #     output::Toggleable,
+/// # };
+/// # use chrono::{Local, TimeDelta, TimeZone};
+/// let start = Local.with_ymd_and_hms(2025, 1, 1, 12, 0, 0).unwrap();
+/// let clock = Arc::new(ManualClock::new(start));
+///
+/// let mut logger = Logger::default();
+/// logger.set_clock(clock.clone());
+/// logger.output.buffer_output.lock().unwrap().enable();
+///
+/// logger.info("First log");
+/// clock.advance(TimeDelta::seconds(5));
+/// logger.info("Second log");
+///
+/// let buffer_output = logger.output.buffer_output.lock().unwrap();
+/// let logs = buffer_output.get_log_buffer();
+/// assert_eq!(logs[0].datetime, start);
+/// assert_eq!(logs[1].datetime, start + TimeDelta::seconds(5));
+/// ```
+#[derive(Debug)]
+pub struct ManualClock {
+    now: Mutex<DateTime<Local>>,
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Local> {
+        Local::now()
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> DateTime<Local> {
+        *self.now.lock().unwrap()
+    }
+}
+
+impl ManualClock {
+    /// Returns a `ManualClock` showing the given date and time.
+    pub fn new(now: DateTime<Local>) -> Self {
+        ManualClock {
+            now: Mutex::new(now),
+        }
+    }
+
+    /// Sets the date and time the clock shows.
+    pub fn set(&self, now: DateTime<Local>) {
+        *self.now.lock().unwrap() = now;
+    }
+
+    /// Moves the clock forward (or backward, for negative values) by the given
+    /// amount of time.
+    pub fn advance(&self, delta: TimeDelta) {
+        let mut now = self.now.lock().unwrap();
+        *now += delta;
+    }
+}
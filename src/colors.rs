@@ -127,6 +127,37 @@ pub fn color_text(text: &str, color: Color) -> String {
     }
 }
 
+/// Returns the ANSII escape code of a `Color` without allocating.
+pub(crate) fn color_code(color: &Color) -> &str {
+    match color {
+        Color::Custom(s) => s,
+        _ => COLOR_MAP[&(color.clone().into())],
+    }
+}
+
+/// Returns `text` with all ANSII escape sequences removed.
+pub(crate) fn strip_ansi_codes(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut char_iter = text.chars().peekable();
+
+    while let Some(c) = char_iter.next() {
+        if c != '\x1b' {
+            result.push(c);
+            continue;
+        }
+
+        // Skip a CSI sequence up to and including its final byte
+        if char_iter.next_if_eq(&'[').is_some() {
+            for nc in char_iter.by_ref() {
+                if ('@'..='~').contains(&nc) {
+                    break;
+                }
+            }
+        }
+    }
+    result
+}
+
 impl Display for Color {
     fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
         let level_str = match self {
@@ -5,15 +5,22 @@
 /// output streams' behavior.
 use serde::{
     Serialize,
-    Deserialize
+    Serializer,
+    Deserialize,
+    Deserializer,
+    de::Error as DeError,
 };
-use std::fmt::{
-    Display,
-    Formatter
+use std::{
+    borrow::Cow,
+    fmt::{
+        Display,
+        Formatter
+    },
 };
 use chrono::{
     Local,
-    DateTime
+    DateTime,
+    SecondsFormat
 };
 
 use crate::Error;
@@ -43,7 +50,10 @@ pub enum Verbosity {
 }
 
 /// Defines the policy for handling log file flushing when a `FileStream`
-/// instance is dropped.
+/// instance is dropped or shut down.
+///
+/// The policy decides what happens to the logs still in the buffer when the
+/// log file is locked, and where they go when writing them fails.
 ///
 /// # Examples
 ///
@@ -56,7 +66,19 @@ pub enum Verbosity {
 /// let mut file_stream = FileStream::default();
 /// file_stream.set_on_drop_policy(OnDropPolicy::IgnoreLogFileLock);
 /// ```
-#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default,
+///
+/// Waiting up to half a second for other processes to release the log file:
+/// ```rust
+/// # use prettylogger::{
+/// #     output::FileStream,
+/// #     config::OnDropPolicy,
+/// # };
+/// let mut file_stream = FileStream::default();
+/// file_stream.set_on_drop_policy(OnDropPolicy::WaitForLock {
+///     timeout_ms: 500,
+/// });
+/// ```
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default,
     Serialize, Deserialize)]
 pub enum OnDropPolicy {
     /// Ignore the log file lock and write to the file anyway. This may cause
@@ -66,8 +88,300 @@ pub enum OnDropPolicy {
     /// Respect the log file lock and don't write to the log file. This may
     /// cause data loss
     DiscardLogBuffer,
+    /// Append the logs to the file at the given path, creating it if needed,
+    /// when the log file is locked or can't be written to
+    FallbackFile(String),
+    /// Print the logs to `stderr` when the log file is locked or can't be
+    /// written to
+    DumpToStderr,
+    /// Ignore the log file lock and wait up to `timeout_ms` milliseconds for
+    /// other processes to release the advisory lock on the log file
+    WaitForLock {
+        /// How long to wait for the lock, in milliseconds
+        timeout_ms: u64,
+    },
+}
+
+/// Defines how `LogOutput` handles logs that could not be written to the log
+/// file (e.g., because the disk is full or the log directory was deleted).
+///
+/// # Examples
+///
+/// Printing logs that could not be written to the log file to `stderr`:
+/// ```rust
+/// # use prettylogger::{
+/// #     output::LogOutput,
+/// #     config::OnErrorPolicy,
+/// # };
+/// let mut log_output = LogOutput::default();
+/// log_output.set_on_error_policy(OnErrorPolicy::FailoverToStderr);
+/// ```
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default,
+    Serialize, Deserialize)]
+pub enum OnErrorPolicy {
+    /// Discard the logs without any notice
+    Ignore,
+    #[default]
+    /// Print a notice to `stderr` the first time writing fails and discard
+    /// the logs
+    ReportOnce,
+    /// Pass the error and the logs to the callback set with
+    /// `LogOutput::set_error_callback`
+    Callback,
+    /// Print the logs to `stderr` instead, unless the `stderr` output already
+    /// printed them
+    FailoverToStderr,
+}
+
+/// Defines whether `FileStream` writes to the log file on the logging thread
+/// or hands the logs over to a dedicated writer thread.
+///
+/// # Examples
+///
+/// Writing logs on a background thread:
+/// ```rust
+/// # use prettylogger::{
+/// #     output::FileStream,
+/// #     config::WriteMode,
+/// # };
+/// let mut file_stream = FileStream::default();
+/// file_stream.set_write_mode(WriteMode::Asynchronous);
+/// ```
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default,
+    Serialize, Deserialize)]
+pub enum WriteMode {
+    #[default]
+    /// Write flushed logs to the log file right away, blocking the logging
+    /// thread until the write completes
+    Synchronous,
+    /// Queue flushed logs for a writer thread, which writes them to the log
+    /// file in the background
+    Asynchronous,
+}
+
+/// Defines when `FileStream` waits for written logs to reach the disk
+/// (`fdatasync`), so they survive a power loss.
+///
+/// # Examples
+///
+/// Syncing the log file on every flush:
+/// ```rust
+/// # use prettylogger::{
+/// #     output::FileStream,
+/// #     config::SyncPolicy,
+/// # };
+/// let mut file_stream = FileStream::default();
+/// file_stream.set_sync_policy(SyncPolicy::EveryFlush);
+/// ```
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default,
+    Serialize, Deserialize)]
+pub enum SyncPolicy {
+    #[default]
+    /// Leave it to the operating system to write the logs to the disk
+    Never,
+    /// Sync the log file every time logs are written to it
+    EveryFlush,
+    /// Sync the log file only when the written logs include an error or a
+    /// fatal error
+    OnError,
+}
+
+/// Defines whether `FileStream` takes an OS-level lock on the log file while
+/// writing to it.
+///
+/// This is unrelated to `FileStream::lock_file`, which only pauses writing
+/// within the process.
+///
+/// # Examples
+///
+/// Sharing a log file between processes:
+/// ```rust
+/// # use prettylogger::{
+/// #     output::FileStream,
+/// #     config::FileLockMode,
+/// # };
+/// let mut file_stream = FileStream::default();
+/// file_stream.set_file_lock_mode(FileLockMode::Advisory);
+/// ```
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default,
+    Serialize, Deserialize)]
+pub enum FileLockMode {
+    #[default]
+    /// Write without locking the log file
+    Disabled,
+    /// Hold an exclusive advisory lock (`flock` on Unix, `LockFileEx` on
+    /// Windows) on the log file while writing, waiting for other processes
+    /// to release theirs. Only processes taking the lock as well are kept
+    /// from interleaving their writes
+    Advisory,
+}
+
+/// Defines what `FileStream` does with flushed logs when the queue of its
+/// writer thread is full.
+///
+/// # Examples
+///
+/// Dropping the oldest queued logs to make room for new ones:
+/// ```rust
+/// # use prettylogger::{
+/// #     output::FileStream,
+/// #     config::BackpressurePolicy,
+/// # };
+/// let mut file_stream = FileStream::default();
+/// file_stream.set_backpressure_policy(BackpressurePolicy::DropOldest);
+/// ```
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default,
+    Serialize, Deserialize)]
+pub enum BackpressurePolicy {
+    #[default]
+    /// Block the logging thread until the writer thread catches up
+    Block,
+    /// Discard the logs that don't fit in the queue
+    DropNewest,
+    /// Discard the oldest queued logs to make room
+    DropOldest,
+}
+
+/// Defines where `FailoverStream` sends logs while its primary log file can't
+/// be written to.
+///
+/// # Examples
+///
+/// Diverting logs to an alternate log file:
+/// ```rust
+/// # use prettylogger::{
+/// #     output::FailoverStream,
+/// #     config::FailoverTarget,
+/// # };
+/// let mut failover_output = FailoverStream::default();
+/// failover_output.set_fallback(
+///     FailoverTarget::File(String::from("/tmp/fallback.log")));
+/// ```
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default,
+    Serialize, Deserialize)]
+pub enum FailoverTarget {
+    #[default]
+    /// Print the logs to `stderr`
+    Stderr,
+    /// Append the logs to the file at the given path, creating it if needed
+    File(String),
+}
+
+/// Defines how `LogFormatter` lays out messages spanning multiple lines.
+///
+/// # Examples
+///
+/// Aligning continuation lines with the start of the message:
+/// ```rust
+/// # use prettylogger::{
+/// #     format::LogFormatter,
+/// #     config::{LogStruct, MultilineMode},
+/// # };
+/// let mut formatter = LogFormatter::default();
+/// formatter.set_multiline_mode(MultilineMode::Indent);
+///
+/// // The second line is printed right under "First line"
+/// let log = LogStruct::error("First line\nSecond line");
+/// print!("{}", formatter.format_log(&log));
+/// ```
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default,
+    Serialize, Deserialize)]
+pub enum MultilineMode {
+    #[default]
+    /// Print the message as-is, leaving continuation lines flush left
+    Verbatim,
+    /// Indent continuation lines so they align with the start of the message
+    Indent,
+    /// Format every line of the message as a separate log
+    RepeatHeader,
+}
+
+/// Defines whether and where `LogFormatter` soft-wraps messages printed to a
+/// terminal.
+///
+/// Wrapping happens at word boundaries, with the wrapped lines indented to
+/// align with the start of the message. It is only applied to logs printed
+/// to `stderr`, so log files and other outputs are left untouched.
+///
+/// # Examples
+///
+/// Wrapping messages at the width of the terminal:
+/// ```rust
+/// # use prettylogger::{
+/// #     format::LogFormatter,
+/// #     config::LineWrap,
+/// # };
+/// let mut formatter = LogFormatter::default();
+/// formatter.set_line_wrap(LineWrap::TerminalWidth);
+/// ```
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default,
+    Serialize, Deserialize)]
+pub enum LineWrap {
+    #[default]
+    /// Don't wrap messages
+    Disabled,
+    /// Wrap messages at the width of the terminal `stderr` is attached to.
+    /// Messages are not wrapped when `stderr` is not a terminal
+    TerminalWidth,
+    /// Wrap messages at a fixed number of columns
+    Fixed(usize),
+}
+
+/// Defines the timezone `LogFormatter` renders timestamps in.
+///
+/// # Examples
+///
+/// Rendering timestamps in UTC:
+/// ```rust
+/// # use prettylogger::{
+/// #     format::LogFormatter,
+/// #     config::Timezone,
+/// # };
+/// let mut formatter = LogFormatter::default();
+/// formatter.set_timezone(Timezone::Utc)
+///     .expect("Failed to set the timezone!");
+/// ```
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default,
+    Serialize, Deserialize)]
+pub enum Timezone {
+    #[default]
+    /// The local timezone of the machine
+    Local,
+    /// Coordinated Universal Time
+    Utc,
+    /// A fixed offset from UTC in seconds, positive east of Greenwich (e.g.,
+    /// `3600` for UTC+01:00). Must be less than 24 hours in either direction
+    FixedOffset(i32),
 }
 
+/// Defines what the timestamp (`%d`) of a log formatted by `LogFormatter`
+/// shows.
+///
+/// Relative timestamps are rendered as seconds with millisecond precision,
+/// e.g. `+0.153s`, and don't use the datetime format.
+///
+/// # Examples
+///
+/// Showing the time elapsed since the previous log:
+/// ```rust
+/// # use prettylogger::{
+/// #     format::LogFormatter,
+/// #     config::TimestampMode,
+/// # };
+/// let mut formatter = LogFormatter::default();
+/// formatter.set_timestamp_mode(TimestampMode::SincePrevious);
+/// ```
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default,
+    Serialize, Deserialize)]
+pub enum TimestampMode {
+    #[default]
+    /// The date and time of the log, formatted with the datetime format
+    Absolute,
+    /// The time elapsed since the formatter was created
+    SinceStart,
+    /// The time elapsed since the previous log
+    SincePrevious,
+}
 
 /// Represents different types of log messages.
 ///
@@ -116,17 +430,75 @@ pub enum LogType {
 ///     .format_log(&LogStruct::error("Much bad!"));
 /// print!("{}", &log);
 /// ```
+///
+/// # Serialization
+///
+/// `LogStruct` serializes to an object with the following fields:
+/// * `version`: The version of this schema (`LOG_SCHEMA_VERSION`).
+/// * `datetime`: The date and time as an RFC 3339 string with the UTC offset
+///   it was recorded at (e.g., `"2025-01-01T12:00:00.153+01:00"`).
+/// * `log_type`: The name of the `LogType` variant (e.g., `"Warning"`).
+/// * `message`: The log message.
+///
+/// Fields may be added in future versions without changing the version, and
+/// unknown fields are ignored when deserializing, so older readers can read
+/// logs written by newer ones. The version only changes when the meaning of
+/// an existing field does, and logs with a newer version than
+/// `LOG_SCHEMA_VERSION` are rejected. A missing version is read as `1`.
+///
+/// The deserialized `datetime` is converted to the local timezone, keeping the
+/// point in time it refers to.
+///
+/// Sending a log as JSON:
+/// ```
+/// # use prettylogger::config::LogStruct;
+/// let log = LogStruct::warning("Disk almost full");
+///
+/// // {"version":1,"datetime":"2025-01-01T12:00:00.153+01:00",
+/// //  "log_type":"Warning","message":"Disk almost full"}
+/// let json = serde_json::to_string(&log)
+///     .expect("Failed to serialize the log!");
+///
+/// let received: LogStruct = serde_json::from_str(&json)
+///     .expect("Failed to deserialize the log!");
+/// assert_eq!(received, log);
+/// ```
 #[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
 pub struct LogStruct {
     /// The log message
     pub message: String,
     /// The type of the log (e.g., `Debug`, `Info`, `Warning`, etc.)
     pub log_type: LogType,
-    /// The date and time at which the log struct was instantiated
+    /// The date and time at which the log struct was instantiated.
+    ///
+    /// This is an absolute point in time: `LogFormatter` converts it to its
+    /// configured `Timezone` when rendering, regardless of the local offset
+    /// stored alongside it.
     pub datetime: DateTime<Local>,
 }
 
 impl LogStruct {
+    /// Returns a `LogStruct` with the given type and date and time.
+    ///
+    /// # Examples
+    ///
+    /// Creating a log with a fixed timestamp:
+    /// ```
+    /// # use prettylogger::config::{LogStruct, LogType};
+    /// # use chrono::{Local, TimeZone};
+    /// let datetime = Local.with_ymd_and_hms(2025, 1, 1, 12, 0, 0).unwrap();
+    /// let log = LogStruct::new("This is an old log!", LogType::Info,
+    ///     datetime);
+    /// ```
+    pub fn new(message: &str, log_type: LogType, datetime: DateTime<Local>)
+    -> LogStruct {
+        LogStruct {
+            message: message.to_string(),
+            log_type,
+            datetime,
+        }
+    }
+
     /// Returns a `LogStruct` with **debug** preset applied.
     ///
     /// # Examples
@@ -137,11 +509,7 @@ impl LogStruct {
     /// let debug_log = LogStruct::debug("This is a debug log!");
     /// ```
     pub fn debug(message: &str) -> LogStruct {
-        LogStruct {
-            message: message.to_string(),
-            log_type: LogType::Debug,
-            datetime: Local::now(),
-        }
+        LogStruct::new(message, LogType::Debug, Local::now())
     }
 
     /// Returns a `LogStruct` with **info** preset applied.
@@ -154,11 +522,7 @@ impl LogStruct {
     /// let info_log = LogStruct::info("This is an info log!");
     /// ```
     pub fn info(message: &str) -> LogStruct {
-        LogStruct {
-            message: message.to_string(),
-            log_type: LogType::Info,
-            datetime: Local::now(),
-        }
+        LogStruct::new(message, LogType::Info, Local::now())
     }
 
     /// Returns a `LogStruct` with **warning** preset applied.
@@ -171,11 +535,7 @@ impl LogStruct {
     /// let warning_log = LogStruct::warning("This is a warning!");
     /// ```
     pub fn warning(message: &str) -> LogStruct {
-        LogStruct {
-            message: message.to_string(),
-            log_type: LogType::Warning,
-            datetime: Local::now(),
-        }
+        LogStruct::new(message, LogType::Warning, Local::now())
     }
 
     /// Returns a `LogStruct` with **error** preset applied.
@@ -188,11 +548,7 @@ impl LogStruct {
     /// let error_log = LogStruct::error("This is an error!");
     /// ```
     pub fn error(message: &str) -> LogStruct {
-        LogStruct {
-            message: message.to_string(),
-            log_type: LogType::Err,
-            datetime: Local::now(),
-        }
+        LogStruct::new(message, LogType::Err, Local::now())
     }
 
     /// Returns a `LogStruct` with **fatal error** preset applied.
@@ -205,11 +561,58 @@ impl LogStruct {
     /// let fatal_log = LogStruct::fatal_error("This is a fatal error!");
     /// ```
     pub fn fatal_error(message: &str) -> LogStruct {
-        LogStruct {
-            message: message.to_string(),
-            log_type: LogType::FatalError,
-            datetime: Local::now(),
+        LogStruct::new(message, LogType::FatalError, Local::now())
+    }
+}
+
+/// The version of the schema `LogStruct` is serialized with.
+pub const LOG_SCHEMA_VERSION: u32 = 1;
+
+/// The serialized form of a `LogStruct`.
+#[derive(Serialize, Deserialize)]
+struct LogRecord<'a> {
+    #[serde(default = "default_log_schema_version")]
+    version: u32,
+    datetime: Cow<'a, str>,
+    log_type: LogType,
+    message: Cow<'a, str>,
+}
+
+fn default_log_schema_version() -> u32 {
+    1
+}
+
+impl Serialize for LogStruct {
+    fn serialize<S: Serializer>(&self, serializer: S)
+    -> Result<S::Ok, S::Error> {
+        LogRecord {
+            version: LOG_SCHEMA_VERSION,
+            datetime: Cow::Owned(
+                self.datetime.to_rfc3339_opts(SecondsFormat::AutoSi, false)),
+            log_type: self.log_type,
+            message: Cow::Borrowed(&self.message),
+        }.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for LogStruct {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D)
+    -> Result<Self, D::Error> {
+        let record = LogRecord::deserialize(deserializer)?;
+        if record.version > LOG_SCHEMA_VERSION {
+            return Err(D::Error::custom(format!(
+                "Unsupported log schema version {} (expected at most {})!",
+                record.version, LOG_SCHEMA_VERSION)));
         }
+        let datetime = DateTime::parse_from_rfc3339(&record.datetime)
+            .map_err(|e| D::Error::custom(format!(
+                "Invalid log datetime '{}': {e}", record.datetime)))?;
+
+        Ok(LogStruct {
+            message: record.message.into_owned(),
+            log_type: record.log_type,
+            datetime: datetime.with_timezone(&Local),
+        })
     }
 }
 
@@ -265,14 +668,122 @@ impl AsRef<str> for Verbosity {
 
 impl Display for OnDropPolicy {
     fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
-        let level_str = match *self {
-            OnDropPolicy::IgnoreLogFileLock => "IgnoreLogFileLock",
-            OnDropPolicy::DiscardLogBuffer => "DiscardLogBuffer",
+        match self {
+            OnDropPolicy::IgnoreLogFileLock => write!(f, "IgnoreLogFileLock"),
+            OnDropPolicy::DiscardLogBuffer => write!(f, "DiscardLogBuffer"),
+            OnDropPolicy::FallbackFile(path) =>
+                write!(f, "FallbackFile('{path}')"),
+            OnDropPolicy::DumpToStderr => write!(f, "DumpToStderr"),
+            OnDropPolicy::WaitForLock { timeout_ms } =>
+                write!(f, "WaitForLock({timeout_ms} ms)"),
+        }
+    }
+}
+
+impl Display for OnErrorPolicy {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        let policy_str = match *self {
+            OnErrorPolicy::Ignore => "Ignore",
+            OnErrorPolicy::ReportOnce => "ReportOnce",
+            OnErrorPolicy::Callback => "Callback",
+            OnErrorPolicy::FailoverToStderr => "FailoverToStderr",
         };
-        write!(f, "{level_str}")
+        write!(f, "{policy_str}")
+    }
+}
+
+impl Display for WriteMode {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        let mode_str = match *self {
+            WriteMode::Synchronous => "Synchronous",
+            WriteMode::Asynchronous => "Asynchronous",
+        };
+        write!(f, "{mode_str}")
+    }
+}
+
+impl Display for SyncPolicy {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        let policy_str = match *self {
+            SyncPolicy::Never => "Never",
+            SyncPolicy::EveryFlush => "EveryFlush",
+            SyncPolicy::OnError => "OnError",
+        };
+        write!(f, "{policy_str}")
+    }
+}
+
+impl Display for FileLockMode {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        let mode_str = match *self {
+            FileLockMode::Disabled => "Disabled",
+            FileLockMode::Advisory => "Advisory",
+        };
+        write!(f, "{mode_str}")
     }
 }
 
+impl Display for BackpressurePolicy {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        let policy_str = match *self {
+            BackpressurePolicy::Block => "Block",
+            BackpressurePolicy::DropNewest => "DropNewest",
+            BackpressurePolicy::DropOldest => "DropOldest",
+        };
+        write!(f, "{policy_str}")
+    }
+}
+
+impl Display for FailoverTarget {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            FailoverTarget::Stderr => write!(f, "Stderr"),
+            FailoverTarget::File(path) => write!(f, "File('{path}')"),
+        }
+    }
+}
+
+impl Display for MultilineMode {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        let mode_str = match *self {
+            MultilineMode::Verbatim => "Verbatim",
+            MultilineMode::Indent => "Indent",
+            MultilineMode::RepeatHeader => "RepeatHeader",
+        };
+        write!(f, "{mode_str}")
+    }
+}
+
+impl Display for LineWrap {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match *self {
+            LineWrap::Disabled => write!(f, "Disabled"),
+            LineWrap::TerminalWidth => write!(f, "TerminalWidth"),
+            LineWrap::Fixed(width) => write!(f, "Fixed({width})"),
+        }
+    }
+}
+
+impl Display for Timezone {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match *self {
+            Timezone::Local => write!(f, "Local"),
+            Timezone::Utc => write!(f, "Utc"),
+            Timezone::FixedOffset(offset) => write!(f, "FixedOffset({offset})"),
+        }
+    }
+}
+
+impl Display for TimestampMode {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        let mode_str = match *self {
+            TimestampMode::Absolute => "Absolute",
+            TimestampMode::SinceStart => "SinceStart",
+            TimestampMode::SincePrevious => "SincePrevious",
+        };
+        write!(f, "{mode_str}")
+    }
+}
 
 impl TryFrom<i32> for LogType {
     type Error = Error;
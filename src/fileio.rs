@@ -1,6 +1,24 @@
 use std::{
-    fs::OpenOptions,
-    io::Write,
+    fs::{
+        self,
+        File,
+        OpenOptions
+    },
+    io::{
+        self,
+        BufWriter,
+        ErrorKind,
+        Write
+    },
+    sync::atomic::{
+        AtomicUsize,
+        Ordering
+    },
+    thread,
+    time::{
+        Duration,
+        Instant
+    },
 };
 
 use crate::Error;
@@ -22,9 +40,189 @@ pub(crate) fn overwrite_file(path: &str, content: &str) -> Result<(), Error> {
 
 }
 
-/// Appends a string to the given file.
-pub(crate) fn append_to_file(path: &str, content: &str) -> Result<(), Error> {
+/// Incremented to make every open `LogFile` reopen before its next write.
+pub(crate) static REOPEN_GENERATION: AtomicUsize = AtomicUsize::new(0);
+
+/// How logs are written to a `LogFile`.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub(crate) struct WriteOptions {
+    /// Wait for the logs to reach the disk (`fdatasync`).
+    pub(crate) sync_data: bool,
+    /// Reopen the file if its path points to a different file.
+    pub(crate) detect_rotation: bool,
+    /// Hold an advisory lock on the file while writing, so other processes
+    /// taking it don't interleave their writes with ours.
+    pub(crate) lock_file: bool,
+    /// How long to wait for the advisory lock before giving up. Waits
+    /// indefinitely when `None`.
+    pub(crate) lock_timeout: Option<Duration>,
+}
+
+/// How often a lock with a timeout is retried while another process holds it.
+const LOCK_RETRY_INTERVAL: Duration = Duration::from_millis(10);
+
+/// A log file kept open between writes, so it doesn't have to be reopened
+/// every time logs are flushed.
+pub(crate) struct LogFile {
+    file: BufWriter<File>,
+    /// The `REOPEN_GENERATION` the file was opened in.
+    generation: usize,
+}
+
+impl LogFile {
+    /// Opens an existing file for appending.
+    pub(crate) fn open(path: &str) -> Result<LogFile, Error> {
+        let generation = REOPEN_GENERATION.load(Ordering::Acquire);
+        match OpenOptions::new()
+            .append(true)
+            .open(path) {
+                Ok(file) => Ok(LogFile {
+                    file: BufWriter::new(file),
+                    generation,
+                }),
+                Err(e) => Err(Error::new(&e.to_string()))
+            }
+    }
+
+    /// Returns whether the file should be reopened, either because a reopen
+    /// of every log file was requested, because the open file was deleted,
+    /// or (with `detect_rotation` set) because `path` now points to a
+    /// different file.
+    pub(crate) fn is_stale(&self, path: &str, detect_rotation: bool) -> bool {
+        if self.generation != REOPEN_GENERATION.load(Ordering::Acquire) {
+            return true;
+        }
+        if self.is_deleted(path) {
+            return true;
+        }
+        return detect_rotation && self.is_replaced(path);
+    }
+
+    /// Returns whether the open file was deleted. Writes to a deleted file
+    /// succeed but are lost, so it has to be reopened for the missing file to
+    /// be reported.
+    #[cfg(unix)]
+    fn is_deleted(&self, _path: &str) -> bool {
+        use std::os::unix::fs::MetadataExt;
+
+        match self.file.get_ref().metadata() {
+            Ok(metadata) => metadata.nlink() == 0,
+            Err(_) => true,
+        }
+    }
+
+    #[cfg(not(unix))]
+    fn is_deleted(&self, path: &str) -> bool {
+        !std::path::Path::new(path).exists()
+    }
+
+    /// Returns whether `path` points to a different file (device and inode)
+    /// than the open one. A missing path counts too, so reopening fails and
+    /// the error is reported instead of logs going to the moved file.
+    #[cfg(unix)]
+    fn is_replaced(&self, path: &str) -> bool {
+        use std::os::unix::fs::MetadataExt;
+
+        let path_metadata = match std::fs::metadata(path) {
+            Ok(metadata) => metadata,
+            Err(_) => return true,
+        };
+        match self.file.get_ref().metadata() {
+            Ok(metadata) => {
+                metadata.dev() != path_metadata.dev() ||
+                    metadata.ino() != path_metadata.ino()
+            },
+            Err(_) => true,
+        }
+    }
+
+    #[cfg(not(unix))]
+    fn is_replaced(&self, _path: &str) -> bool {
+        false
+    }
+
+    /// Appends a string to the file.
+    pub(crate) fn append(&mut self, content: &str, options: WriteOptions)
+    -> Result<(), Error> {
+        let lock_result = if options.lock_file {
+            self.lock(options.lock_timeout)
+        }
+        else {
+            Ok(())
+        };
+        if let Err(e) = lock_result {
+            return Err(Error::new(&e.to_string()));
+        }
+
+        let mut result = self.file.write_all(content.as_bytes())
+            .and_then(|_| self.file.flush());
+        if result.is_ok() && options.sync_data {
+            result = self.file.get_ref().sync_data();
+        }
+
+        if options.lock_file {
+            let unlock_result = self.file.get_ref().unlock();
+            result = result.and(unlock_result);
+        }
+        match result {
+            Ok(_) => Ok(()),
+            Err(e) => Err(Error::new(&e.to_string()))
+        }
+    }
+
+    /// Takes the advisory lock on the file, giving up once `timeout` passes.
+    fn lock(&self, timeout: Option<Duration>) -> io::Result<()> {
+        let file = self.file.get_ref();
+        let timeout = match timeout {
+            Some(timeout) => timeout,
+            None => return file.lock(),
+        };
+
+        let deadline = Instant::now() + timeout;
+        loop {
+            match file.try_lock() {
+                Ok(()) => return Ok(()),
+                Err(fs::TryLockError::Error(e)) => return Err(e),
+                Err(fs::TryLockError::WouldBlock) => {},
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(io::Error::new(ErrorKind::TimedOut,
+                    "Timed out waiting for the log file lock!"));
+            }
+            thread::sleep(LOCK_RETRY_INTERVAL.min(deadline - now));
+        }
+    }
+}
+
+/// Appends a string to a log file, opening it first if `handle` is empty or
+/// the open file is stale.
+///
+/// The handle is closed when writing fails, so the file is reopened on the
+/// next write.
+pub(crate) fn append_with_handle(handle: &mut Option<LogFile>, path: &str,
+content: &str, options: WriteOptions) -> Result<(), Error> {
+    let is_stale = handle.as_ref().is_some_and(|log_file| {
+        log_file.is_stale(path, options.detect_rotation)
+    });
+    if handle.is_none() || is_stale {
+        *handle = None;
+        *handle = Some(LogFile::open(path)?);
+    }
+    let log_file = handle.as_mut().expect("Log file was opened");
+
+    let result = log_file.append(content, options);
+    if result.is_err() {
+        *handle = None;
+    }
+    return result;
+}
+
+/// Appends a string to the given file, creating the file if it doesn't exist.
+pub(crate) fn create_or_append_to_file(path: &str, content: &str)
+-> Result<(), Error> {
     match OpenOptions::new()
+        .create(true)
         .append(true)
         .open(path) {
             Ok(mut file) => {
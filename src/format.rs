@@ -3,24 +3,66 @@
 
 /// Contains `LogFormatter`, used to create formatted log messages from raw log
 /// structs.
-use serde::{
-    Serialize,
-    Deserialize
+use std::{
+    env,
+    fmt::Write,
+    io::stderr,
 };
+
 use chrono::{
+    DateTime,
+    FixedOffset,
     Local,
-    DateTime
+    TimeDelta,
+    Utc
+};
+use serde::{
+    Serialize,
+    Deserialize,
+    Deserializer,
+    de::Error as DeError,
 };
+use unicode_width::UnicodeWidthStr;
 
 use crate::{
     LogType, Error,
     colors::{
         Color,
-        color_text
+        RESET,
+        color_code,
+        strip_ansi_codes
+    },
+    config::{
+        LineWrap,
+        LogStruct,
+        MultilineMode,
+        TimestampMode,
+        Timezone
+    },
+    pattern::{
+        DatetimePattern,
+        LogPattern,
+        Segment
     },
-    config::LogStruct,
+    runtime::RuntimeState,
 };
 
+/// Datetime format producing RFC 3339 timestamps, e.g.
+/// `2025-01-01T12:00:00+01:00`.
+pub const DATETIME_RFC3339: &str = "%Y-%m-%dT%H:%M:%S%:z";
+
+/// Datetime format producing ISO 8601 timestamps with millisecond precision,
+/// e.g. `2025-01-01T12:00:00.153+01:00`.
+pub const DATETIME_ISO8601_MILLIS: &str = "%Y-%m-%dT%H:%M:%S%.3f%:z";
+
+/// Datetime format producing the number of seconds since the Unix epoch, e.g.
+/// `1735729200`.
+pub const DATETIME_EPOCH_SECONDS: &str = "%s";
+
+/// Datetime format producing the number of milliseconds since the Unix epoch,
+/// e.g. `1735729200153`.
+pub const DATETIME_EPOCH_MILLIS: &str = "%s%3f";
+
 /// Formats raw log structs into log messages by applying both the log
 /// message's configuration and the formatter's own settings.
 ///
@@ -61,44 +103,187 @@ pub struct LogFormatter {
     pub(crate) error_header: String,
     pub(crate) fatal_header: String,
 
-    pub(crate) log_format: String,
-    pub(crate) datetime_format: String,
+    #[serde(deserialize_with = "deserialize_log_format")]
+    pub(crate) log_format: LogPattern,
+    pub(crate) datetime_format: DatetimePattern,
+
+    #[serde(default, deserialize_with = "deserialize_log_format_override")]
+    pub(crate) debug_log_format: Option<LogPattern>,
+    #[serde(default, deserialize_with = "deserialize_log_format_override")]
+    pub(crate) info_log_format: Option<LogPattern>,
+    #[serde(default, deserialize_with = "deserialize_log_format_override")]
+    pub(crate) warning_log_format: Option<LogPattern>,
+    #[serde(default, deserialize_with = "deserialize_log_format_override")]
+    pub(crate) error_log_format: Option<LogPattern>,
+    #[serde(default, deserialize_with = "deserialize_log_format_override")]
+    pub(crate) fatal_log_format: Option<LogPattern>,
+
+    #[serde(default)]
+    pub(crate) multiline_mode: MultilineMode,
+    #[serde(default)]
+    pub(crate) line_wrap: LineWrap,
+    #[serde(default, deserialize_with = "deserialize_timezone")]
+    pub(crate) timezone: Timezone,
+    #[serde(default)]
+    pub(crate) timestamp_mode: TimestampMode,
 
     #[serde(skip)]
-    pub(crate) show_datetime: Option<bool>,
+    pub(crate) timestamps: RuntimeState<TimestampState>,
 }
 
-impl LogFormatter {
-    pub(crate) fn get_datetime_formatted(&mut self, datetime: &DateTime<Local>) -> String {
-        match self.show_datetime {
-            Some(b) => {
-                match b {
-                    true => {
-                        datetime.format(&self.datetime_format)
-                            .to_string()
-                    },
-                    false => {
-                        String::new()
-                    },
-                }
+/// Keeps track of the points in time relative timestamps are measured from.
+#[derive(Clone, Debug)]
+pub(crate) struct TimestampState {
+    /// What `TimestampMode::SinceStart` timestamps are relative to.
+    origin: DateTime<Local>,
+    /// The datetime of the last formatted log.
+    last: Option<DateTime<Local>>,
+    /// What the `TimestampMode::SincePrevious` timestamp of the log passed to
+    /// `LogOutput::out` is relative to, shared by every output formatting it.
+    pinned: Option<DateTime<Local>>,
+}
 
-            },
-            None => {
-                match self.log_format.contains("%d") {
-                    true => {
-                        self.show_datetime = Some(true);
-                        datetime.format(&self.datetime_format)
-                            .to_string()
-                    },
-                    false => {
-                        self.show_datetime = Some(false);
-                        String::new()
-                    },
-                }
+impl Default for TimestampState {
+    fn default() -> Self {
+        TimestampState {
+            origin: Local::now(),
+            last: None,
+            pinned: None,
+        }
+    }
+}
+
+/// Everything needed to render a single log entry.
+struct RenderContext<'a> {
+    log: &'a LogStruct,
+    /// The message, or the line of it, being rendered.
+    message: &'a str,
+    /// The number of columns to wrap the message at.
+    width: Option<usize>,
+    /// How much time passed between the reference point in time and the log,
+    /// when rendering relative timestamps.
+    elapsed: Option<TimeDelta>,
+}
+
+/// Number of columns a tab advances to.
+const TAB_WIDTH: usize = 8;
+
+/// Returns the number of columns `text` takes up when printed, ignoring
+/// ANSII escape codes. Wide characters (e.g., CJK text and emoji) take up two
+/// columns.
+fn display_width(text: &str) -> usize {
+    strip_ansi_codes(text).split('\t').enumerate().fold(0, |width, (i, part)| {
+        let width = if i == 0 {
+            width
+        }
+        else {
+            (width / TAB_WIDTH + 1) * TAB_WIDTH
+        };
+        width + part.width()
+    })
+}
+
+/// Returns the indentation lining text up with the end of `prefix`, keeping
+/// tabs so the alignment holds with any tab width.
+pub(crate) fn indentation(prefix: &str) -> String {
+    strip_ansi_codes(prefix).split('\t')
+        .map(|part| " ".repeat(part.width()))
+        .collect::<Vec<_>>()
+        .join("\t")
+}
+
+/// Returns the width of the terminal `stderr` is attached to.
+///
+/// Falls back to the `COLUMNS` environment variable when the width can't be
+/// queried directly.
+fn terminal_width() -> Option<usize> {
+    match terminal_size::terminal_size_of(stderr()) {
+        Some((terminal_size::Width(width), _)) => Some(width as usize),
+        None => env::var("COLUMNS").ok()?.parse().ok(),
+    }
+}
+
+/// Appends a single line of text to `buf`, breaking it at spaces so it fits
+/// in `width` columns. Wrapped lines start with `indent`.
+fn push_wrapped(buf: &mut String, line: &str, indent: &str, mut column: usize,
+width: usize) {
+    let indent_width = display_width(indent);
+
+    for (i, word) in line.split(' ').enumerate() {
+        let word_width = display_width(word);
+        if i != 0 {
+            if column + 1 + word_width > width && column > indent_width {
+                buf.push('\n');
+                buf.push_str(indent);
+                column = indent_width;
+            }
+            else {
+                buf.push(' ');
+                column += 1;
             }
         }
+        buf.push_str(word);
+        column += word_width;
+    }
+}
+
+/// Compiles a log format, making sure it contains a message placeholder.
+fn compile_log_format(format: &str) -> Result<LogPattern, Error> {
+    let pattern = LogPattern::compile(format)?;
+    if pattern.has_message() {
+        Ok(pattern)
+    }
+    else {
+        Err(Error::new("Expected a message placeholder!"))
+    }
+}
+
+/// Compiles an optional log format override.
+fn compile_log_format_override(format: Option<&str>)
+-> Result<Option<LogPattern>, Error> {
+    match format {
+        Some(format) => Ok(Some(compile_log_format(format)?)),
+        None => Ok(None),
+    }
+}
+
+/// Deserializes a log format from a template, making sure it contains a
+/// message placeholder.
+fn deserialize_log_format<'de, D: Deserializer<'de>>(deserializer: D)
+-> Result<LogPattern, D::Error> {
+    let format = String::deserialize(deserializer)?;
+    compile_log_format(&format).map_err(|e| D::Error::custom(e.message))
+}
+
+/// Deserializes an optional log format override from a template, making sure
+/// it contains a message placeholder.
+fn deserialize_log_format_override<'de, D: Deserializer<'de>>(
+deserializer: D) -> Result<Option<LogPattern>, D::Error> {
+    let format = Option::<String>::deserialize(deserializer)?;
+    compile_log_format_override(format.as_deref())
+        .map_err(|e| D::Error::custom(e.message))
+}
+
+/// Makes sure a fixed UTC offset is less than 24 hours in either direction.
+fn validate_timezone(timezone: Timezone) -> Result<Timezone, Error> {
+    match timezone {
+        Timezone::FixedOffset(offset)
+        if FixedOffset::east_opt(offset).is_none() => {
+            Err(Error::new(&format!(
+                "Invalid UTC offset of {offset} seconds!")))
+        },
+        _ => Ok(timezone),
     }
+}
+
+/// Deserializes a timezone from a template, rejecting invalid offsets.
+fn deserialize_timezone<'de, D: Deserializer<'de>>(deserializer: D)
+-> Result<Timezone, D::Error> {
+    let timezone = Timezone::deserialize(deserializer)?;
+    validate_timezone(timezone).map_err(|e| D::Error::custom(e.message))
+}
 
+impl LogFormatter {
     pub(crate) fn log_header_color(&self, log_type: LogType) -> Color {
         match log_type {
             LogType::Debug => self.debug_color.clone(),
@@ -109,43 +294,47 @@ impl LogFormatter {
         }
     }
 
-    pub(crate) fn colorify(&self, text: &str, color: Color) -> String {
-        if self.log_header_color_enabled {
-            return color_text(text, color);
+    pub(crate) fn log_header(&self, log_type: LogType) -> &str {
+        match log_type {
+            LogType::Debug => &self.debug_header,
+            LogType::Info => &self.info_header,
+            LogType::Warning => &self.warning_header,
+            LogType::Err => &self.error_header,
+            LogType::FatalError => &self.fatal_header,
         }
-        text.to_string()
     }
 
-    pub(crate) fn get_log_type_header(&self, log_type: LogType) -> String {
-        match log_type {
-            LogType::Debug => {
-                self.colorify(&self.debug_header,
-                    self.log_header_color(log_type))
-            }
-            LogType::Info => {
-                self.colorify(&self.info_header,
-                    self.log_header_color(log_type))
-            }
-            LogType::Warning => {
-                self.colorify(&self.warning_header,
-                    self.log_header_color(log_type))
-            }
-            LogType::Err => {
-                self.colorify(&self.error_header,
-                    self.log_header_color(log_type))
-            }
-            LogType::FatalError => {
-                self.colorify(&self.fatal_header,
-                    self.log_header_color(log_type))
-            }
-        }
+    /// Returns the log format used for the given log type, falling back to
+    /// the global log format when there is no override.
+    pub(crate) fn log_pattern(&self, log_type: LogType) -> &LogPattern {
+        let log_format = match log_type {
+            LogType::Debug => &self.debug_log_format,
+            LogType::Info => &self.info_log_format,
+            LogType::Warning => &self.warning_log_format,
+            LogType::Err => &self.error_log_format,
+            LogType::FatalError => &self.fatal_log_format,
+        };
+        log_format.as_ref().unwrap_or(&self.log_format)
     }
 
-    pub(crate) fn get_log_headers(&mut self, log: &LogStruct)
-    -> (String, String) {
-        let header = self.get_log_type_header(log.log_type);
-        let datetime = self.get_datetime_formatted(&log.datetime);
-        (header, datetime)
+    /// Appends the (optionally colored) log type header to `buf`.
+    pub(crate) fn push_log_type_header(&self, buf: &mut String,
+    log_type: LogType) {
+        let header = self.log_header(log_type);
+        let color = self.log_header_color(log_type);
+
+        if header.is_empty() {
+            return;
+        }
+
+        if self.log_header_color_enabled && color != Color::None {
+            buf.push_str(color_code(&color));
+            buf.push_str(header);
+            buf.push_str(RESET);
+        }
+        else {
+            buf.push_str(header);
+        }
     }
 
     /// Returns a log entry from a `LogStruct` based on current `LogFormatter`
@@ -158,32 +347,229 @@ impl LogFormatter {
     /// let log_string = formatter.format_log(&LogStruct::error("Error!"));
     /// ```
     pub fn format_log(&mut self, log: &LogStruct) -> String {
-        let headers = self.get_log_headers(log);
-        let mut result = String::new();
-        let mut char_iter = self
-            .log_format.char_indices().peekable();
-
-        while let Some((_, c)) = char_iter.next() {
-            match c {
-                '%' => {
-                    if let Some((_, nc)) = char_iter.peek() {
-                        match nc {
-                            'h' => result += &headers.0,
-                            'd' => result += &headers.1,
-                            'm' => result += &log.message,
-                            _ => result += &nc.to_string(),
-                        }
-                        char_iter.next();
+        let mut result = String::with_capacity(
+            self.log_format.as_str().len() + log.message.len() + 32);
+        self.format_log_into(log, &mut result);
+        result
+    }
+
+    /// Appends a log entry created from a `LogStruct` to `buf`.
+    ///
+    /// Unlike `format_log`, this method doesn't allocate a new string for
+    /// every log, so a single buffer can be reused across many logs.
+    ///
+    /// # Examples
+    /// ```
+    /// # use prettylogger::{format::LogFormatter, config::LogStruct};
+    /// let mut formatter = LogFormatter::default();
+    /// let mut buf = String::new();
+    ///
+    /// for i in 0..8 {
+    ///     buf.clear();
+    ///     formatter.format_log_into(&LogStruct::info(&format!("Log {i}")),
+    ///         &mut buf);
+    ///     eprint!("{}", &buf);
+    /// }
+    /// ```
+    pub fn format_log_into(&mut self, log: &LogStruct, buf: &mut String) {
+        self.render_log(log, buf, None);
+    }
+
+    /// Returns a log entry from a `LogStruct`, soft-wrapping the message
+    /// according to the `LineWrap` setting.
+    ///
+    /// This is what `StderrStream` uses to print logs; other outputs use
+    /// `format_log`, which never wraps.
+    ///
+    /// # Examples
+    /// ```
+    /// # use prettylogger::{
+    /// #     format::LogFormatter,
+    /// #     config::{LogStruct, LineWrap},
+    /// # };
+    /// let mut formatter = LogFormatter::default();
+    /// formatter.set_line_wrap(LineWrap::Fixed(16));
+    /// # formatter.disable_log_header_color();
+    ///
+    /// let log = formatter.format_log_for_terminal(
+    ///     &LogStruct::info("The quick brown fox"));
+    /// assert_eq!(log, "[INF] The quick\n      brown fox\n");
+    /// ```
+    pub fn format_log_for_terminal(&mut self, log: &LogStruct) -> String {
+        let width = match self.line_wrap {
+            LineWrap::Disabled => None,
+            LineWrap::TerminalWidth => terminal_width(),
+            LineWrap::Fixed(width) => Some(width),
+        };
+
+        let mut result = String::with_capacity(
+            self.log_format.as_str().len() + log.message.len() + 32);
+        self.render_log(log, &mut result, width);
+        result
+    }
+
+    /// Appends a log entry to `buf`, wrapping the message at `width` columns
+    /// if given.
+    fn render_log(&mut self, log: &LogStruct, buf: &mut String,
+    width: Option<usize>) {
+        let elapsed = self.elapsed(log);
+        let segments = self.log_pattern(log.log_type).segments();
+        let mut ctx = RenderContext {
+            log,
+            message: &log.message,
+            width,
+            elapsed,
+        };
+
+        if self.multiline_mode == MultilineMode::RepeatHeader {
+            for line in log.message.split('\n') {
+                ctx.message = line;
+                self.push_segments(segments, &ctx, buf);
+                buf.push('\n');
+            }
+        }
+        else {
+            self.push_segments(segments, &ctx, buf);
+            buf.push('\n');
+        }
+    }
+
+    /// Returns the time elapsed since the point in time the log's relative
+    /// timestamp is measured from, and remembers the log for
+    /// `TimestampMode::SincePrevious`.
+    fn elapsed(&mut self, log: &LogStruct) -> Option<TimeDelta> {
+        let state = &mut self.timestamps;
+        match self.timestamp_mode {
+            TimestampMode::Absolute => None,
+            TimestampMode::SinceStart => Some(log.datetime - state.origin),
+            TimestampMode::SincePrevious => {
+                let reference = match state.pinned {
+                    Some(reference) => reference,
+                    None => {
+                        let reference = state.last.unwrap_or(state.origin);
+                        state.last = Some(log.datetime);
+                        reference
+                    },
+                };
+                Some(log.datetime - reference)
+            },
+        }
+    }
+
+    /// Picks the point in time the `TimestampMode::SincePrevious` timestamp
+    /// of `log` is measured from, so every output formatting it renders the
+    /// same timestamp until `end_log` is called.
+    pub(crate) fn begin_log(&mut self, log: &LogStruct) {
+        let state = &mut self.timestamps;
+        state.pinned = Some(state.last.unwrap_or(state.origin));
+        state.last = Some(log.datetime);
+    }
+
+    /// Makes `TimestampMode::SincePrevious` timestamps relative to the
+    /// previous log again after `begin_log`.
+    pub(crate) fn end_log(&mut self) {
+        self.timestamps.pinned = None;
+    }
+
+    /// Appends rendered segments to `buf`.
+    ///
+    /// Returns whether any of the placeholders rendered non-empty text.
+    fn push_segments(&self, segments: &[Segment], ctx: &RenderContext,
+    buf: &mut String) -> bool {
+        let mut rendered = false;
+        for segment in segments {
+            let start = buf.len();
+            match segment {
+                Segment::Literal(text) => {
+                    buf.push_str(text);
+                    continue;
+                },
+                Segment::Header => {
+                    self.push_log_type_header(buf, ctx.log.log_type);
+                },
+                Segment::Datetime => self.push_datetime(buf, ctx),
+                Segment::Message => {
+                    self.push_message(buf, ctx.message, ctx.width);
+                },
+                Segment::Group(inner) => {
+                    if !self.push_segments(inner, ctx, buf) {
+                        buf.truncate(start);
                     }
-                }
-                _ => {
-                    result += &c.to_string();
-                }
+                },
             }
+            rendered |= buf.len() > start;
         }
+        rendered
+    }
 
-        result += "\n";
-        result
+    /// Appends the log's timestamp to `buf`, either as elapsed time or
+    /// converted to the configured timezone.
+    fn push_datetime(&self, buf: &mut String, ctx: &RenderContext) {
+        if let Some(elapsed) = ctx.elapsed {
+            let sign = if elapsed < TimeDelta::zero() { '-' } else { '+' };
+            let millis = elapsed.num_milliseconds().abs();
+            let _ = write!(buf, "{sign}{}.{:03}s", millis / 1000,
+                millis % 1000);
+            return;
+        }
+
+        let datetime = &ctx.log.datetime;
+        let items = self.datetime_format.items().iter();
+        let _ = match self.timezone {
+            Timezone::Local => write!(buf, "{}",
+                datetime.format_with_items(items)),
+            Timezone::Utc => write!(buf, "{}",
+                datetime.with_timezone(&Utc).format_with_items(items)),
+            Timezone::FixedOffset(offset) => {
+                let offset = FixedOffset::east_opt(offset)
+                    .expect("Offsets are validated when set!");
+                write!(buf, "{}",
+                    datetime.with_timezone(&offset).format_with_items(items))
+            },
+        };
+    }
+
+    /// Appends the message to `buf`, indenting its continuation lines when
+    /// `MultilineMode::Indent` is set and wrapping it at `width` columns if
+    /// given.
+    fn push_message(&self, buf: &mut String, message: &str,
+    width: Option<usize>) {
+        let indent_lines = self.multiline_mode == MultilineMode::Indent;
+        if width.is_none() && (!indent_lines || !message.contains('\n')) {
+            buf.push_str(message);
+            return;
+        }
+
+        // Mirror everything printed before the message on the current line
+        let line_start = buf.rfind('\n').map_or(0, |i| i + 1);
+        let prefix_width = display_width(&buf[line_start..]);
+        let mut indent = indentation(&buf[line_start..]);
+
+        // Don't squeeze wrapped lines into a narrow column when the message
+        // starts far to the right
+        if width.is_some_and(|width| prefix_width * 2 > width) {
+            indent.clear();
+        }
+
+        for (i, line) in message.split('\n').enumerate() {
+            let column = if i == 0 {
+                prefix_width
+            }
+            else if indent_lines {
+                buf.push('\n');
+                buf.push_str(&indent);
+                display_width(&indent)
+            }
+            else {
+                buf.push('\n');
+                0
+            };
+
+            match width {
+                Some(width) => push_wrapped(buf, line, &indent, column, width),
+                None => buf.push_str(line),
+            }
+        }
     }
 
     /// Enables the log headers to have colors.
@@ -246,15 +632,140 @@ impl LogFormatter {
         self.fatal_header = header.to_string();
     }
 
+    /// Sets how messages spanning multiple lines are laid out.
+    ///
+    /// # Examples
+    /// ```
+    /// # use prettylogger::{
+    /// #     format::LogFormatter,
+    /// #     config::{LogStruct, MultilineMode},
+    /// # };
+    /// let mut formatter = LogFormatter::default();
+    /// formatter.set_multiline_mode(MultilineMode::RepeatHeader);
+    /// # formatter.disable_log_header_color();
+    ///
+    /// // Each line gets its own header
+    /// let log = formatter.format_log(&LogStruct::info("First\nSecond"));
+    /// assert_eq!(log, "[INF] First\n[INF] Second\n");
+    /// ```
+    pub fn set_multiline_mode<I: Into<MultilineMode>>(&mut self, mode: I) {
+        self.multiline_mode = mode.into();
+    }
+
+    /// Sets whether and where messages printed to `stderr` are soft-wrapped.
+    ///
+    /// # Examples
+    /// ```
+    /// # use prettylogger::{
+    /// #     format::LogFormatter,
+    /// #     config::LineWrap,
+    /// # };
+    /// let mut formatter = LogFormatter::default();
+    ///
+    /// // Wrap messages at 80 columns
+    /// formatter.set_line_wrap(LineWrap::Fixed(80));
+    /// ```
+    pub fn set_line_wrap<I: Into<LineWrap>>(&mut self, line_wrap: I) {
+        self.line_wrap = line_wrap.into();
+    }
+
+    /// Sets the timezone timestamps are rendered in.
+    ///
+    /// Returns an error when a fixed offset is 24 hours or more.
+    ///
+    /// # Examples
+    /// ```
+    /// # use prettylogger::{
+    /// #     format::LogFormatter,
+    /// #     config::Timezone,
+    /// # };
+    /// let mut formatter = LogFormatter::default();
+    ///
+    /// // Render timestamps in UTC+02:00
+    /// formatter.set_timezone(Timezone::FixedOffset(2 * 3600))
+    ///     .expect("Failed to set the timezone!");
+    /// ```
+    pub fn set_timezone<I: Into<Timezone>>(&mut self, timezone: I)
+    -> Result<(), Error> {
+        self.timezone = validate_timezone(timezone.into())?;
+        Ok(())
+    }
+
+    /// Sets whether timestamps show the date and time of a log or the time
+    /// elapsed since the formatter was created or since the previous log.
+    ///
+    /// # Examples
+    /// ```
+    /// # use prettylogger::{
+    /// #     format::LogFormatter,
+    /// #     config::TimestampMode,
+    /// # };
+    /// let mut formatter = LogFormatter::default();
+    /// formatter.set_log_format("%d %m");
+    ///
+    /// // Timestamps will look like `+0.153s`
+    /// formatter.set_timestamp_mode(TimestampMode::SinceStart);
+    /// ```
+    pub fn set_timestamp_mode<I: Into<TimestampMode>>(&mut self, mode: I) {
+        self.timestamp_mode = mode.into();
+    }
+
+    /// Sets the point in time `TimestampMode::SinceStart` timestamps are
+    /// measured from, and that the first `TimestampMode::SincePrevious`
+    /// timestamp is measured from.
+    ///
+    /// Defaults to the moment the formatter was created.
+    pub fn set_timestamp_origin(&mut self, origin: DateTime<Local>) {
+        self.timestamps.origin = origin;
+        self.timestamps.last = None;
+        self.timestamps.pinned = None;
+    }
+
+    /// Returns the point in time `TimestampMode::SinceStart` timestamps are
+    /// measured from.
+    pub(crate) fn timestamp_origin(&self) -> DateTime<Local> {
+        self.timestamps.origin
+    }
+
     /// Sets datetime format.
-    pub fn set_datetime_format(&mut self, format: &str) {
-        self.datetime_format = String::from(format);
-        self.show_datetime = None;
+    ///
+    /// The format uses `strftime`-like specifiers (see `chrono`'s
+    /// `format::strftime` module). Returns an error naming the first invalid
+    /// specifier and its position, counted in characters from 0.
+    ///
+    /// Some common formats are available as `DATETIME_*` constants.
+    ///
+    /// # Examples
+    /// ```
+    /// # use prettylogger::format::{
+    /// #     LogFormatter,
+    /// #     DATETIME_RFC3339,
+    /// # };
+    /// let mut formatter = LogFormatter::default();
+    ///
+    /// formatter.set_datetime_format("%H:%M:%S")
+    ///     .expect("Failed to set datetime format!");
+    ///
+    /// // Use a preset
+    /// formatter.set_datetime_format(DATETIME_RFC3339)
+    ///     .expect("Failed to set datetime format!");
+    ///
+    /// // Invalid specifiers are rejected
+    /// # let result =
+    /// formatter.set_datetime_format("%H:%M:%Q");
+    /// # assert_eq!(result.unwrap_err().message,
+    /// #     "Invalid datetime specifier '%Q' at position 6!");
+    /// ```
+    pub fn set_datetime_format(&mut self, format: &str) -> Result<(), Error> {
+        self.datetime_format = DatetimePattern::compile(format)?;
+        Ok(())
     }
 
     /// Sets the log format.
     ///
-    /// Returns an error when the `%m` placeholder is missing.
+    /// Returns an error when the `%m` placeholder is missing or the format is
+    /// malformed. Errors about malformed formats include the position of the
+    /// offending `%`, counted in characters from 0.
     ///
     /// There are several placeholders in a log format string:
     /// * `%m`: The log message (this placeholder is mandatory, you will
@@ -262,7 +773,21 @@ impl LogFormatter {
     /// * `%h`: The header indicating the log type (e.g., debug, error, etc.)
     /// * `%d`: The timestamp.
     ///
-    /// You can have multiple placeholders of the same type in a format string.
+    /// And several escape sequences:
+    /// * `%%`: A literal `%`.
+    /// * `%}`: A literal `}`.
+    /// * `%t`: A tab.
+    /// * `%n`: A newline.
+    ///
+    /// Any other character following a `%`, as well as a trailing `%`, is an
+    /// error. You can have multiple placeholders of the same type in a format
+    /// string.
+    ///
+    /// Parts of a format can be wrapped in a conditional group, `%{...}`. A
+    /// group is left out entirely unless at least one placeholder inside it
+    /// renders non-empty text, e.g. `%{[%d] }%m` only prints the brackets
+    /// when the datetime format is not empty. Groups can be nested, and a
+    /// group that is never closed is an error.
     ///
     /// # Examples
     /// ```
@@ -275,21 +800,107 @@ impl LogFormatter {
     /// // Do a nice XML-like format
     /// formatter.set_log_format("<l> <h>%h</h> <m>%m</m> </l>");
     /// print!("{}", formatter.format_log(&LogStruct::debug("Hello, World!")));
+    ///
+    /// // Use escape sequences for a literal percent sign and a tab
+    /// formatter.set_log_format("%h%t100%% %m");
+    ///
+    /// // Only print the timestamp brackets when there is a timestamp
+    /// formatter.set_log_format("%{[%d] }%h: %m");
+    /// formatter.set_datetime_format("");
+    /// # formatter.disable_log_header_color();
+    /// # assert_eq!(formatter.format_log(&LogStruct::debug("Hello, World!")),
+    /// #     "DBG: Hello, World!\n");
+    ///
+    /// // Unknown placeholders are rejected
+    /// # let result =
+    /// formatter.set_log_format("%h %x %m");
+    /// # assert_eq!(result.unwrap_err().message,
+    /// #     "Unknown placeholder '%x' at position 3!");
     /// ```
     pub fn set_log_format(&mut self, format: &str) -> Result<(), Error> {
-        if format.contains("%m") {
-            self.log_format = String::from(format);
-            Ok(())
-        }
-        else {
-            Err(Error::new("Expected a message placeholder!"))
-        }
+        self.log_format = compile_log_format(format)?;
+        Ok(())
+    }
+
+    /// Sets the log format used for **debug logs** instead of the global log
+    /// format. `None` removes the override.
+    ///
+    /// Returns an error when the format is not valid, as with
+    /// `set_log_format`.
+    ///
+    /// # Examples
+    /// ```
+    /// # use prettylogger::format::LogFormatter;
+    /// let mut formatter = LogFormatter::default();
+    ///
+    /// // Give debug logs a terse format
+    /// formatter.set_debug_log_format("%m");
+    ///
+    /// // Go back to the global log format
+    /// formatter.set_debug_log_format(None);
+    /// ```
+    pub fn set_debug_log_format<'a, I: Into<Option<&'a str>>>(&mut self,
+    format: I) -> Result<(), Error> {
+        self.debug_log_format = compile_log_format_override(format.into())?;
+        Ok(())
+    }
+
+    /// Sets the log format used for **info logs** instead of the global log
+    /// format. `None` removes the override.
+    ///
+    /// Returns an error when the format is not valid, as with
+    /// `set_log_format`.
+    pub fn set_info_log_format<'a, I: Into<Option<&'a str>>>(&mut self,
+    format: I) -> Result<(), Error> {
+        self.info_log_format = compile_log_format_override(format.into())?;
+        Ok(())
+    }
+
+    /// Sets the log format used for **warnings** instead of the global log
+    /// format. `None` removes the override.
+    ///
+    /// Returns an error when the format is not valid, as with
+    /// `set_log_format`.
+    pub fn set_warning_log_format<'a, I: Into<Option<&'a str>>>(&mut self,
+    format: I) -> Result<(), Error> {
+        self.warning_log_format = compile_log_format_override(format.into())?;
+        Ok(())
+    }
+
+    /// Sets the log format used for **errors** instead of the global log
+    /// format. `None` removes the override.
+    ///
+    /// Returns an error when the format is not valid, as with
+    /// `set_log_format`.
+    pub fn set_error_log_format<'a, I: Into<Option<&'a str>>>(&mut self,
+    format: I) -> Result<(), Error> {
+        self.error_log_format = compile_log_format_override(format.into())?;
+        Ok(())
+    }
+
+    /// Sets the log format used for **fatal errors** instead of the global
+    /// log format. `None` removes the override.
+    ///
+    /// Returns an error when the format is not valid, as with
+    /// `set_log_format`.
+    ///
+    /// # Examples
+    /// ```
+    /// # use prettylogger::format::LogFormatter;
+    /// let mut formatter = LogFormatter::default();
+    ///
+    /// // Give fatal errors a richer format
+    /// formatter.set_fatal_log_format("[%h] %d: %m");
+    /// ```
+    pub fn set_fatal_log_format<'a, I: Into<Option<&'a str>>>(&mut self,
+    format: I) -> Result<(), Error> {
+        self.fatal_log_format = compile_log_format_override(format.into())?;
+        Ok(())
     }
 }
 
 impl Default for LogFormatter {
     fn default() -> LogFormatter {
-        let log_format = String::from("[%h] %m");
         LogFormatter {
             log_header_color_enabled: true,
 
@@ -305,10 +916,23 @@ impl Default for LogFormatter {
             error_header: String::from("ERR"),
             fatal_header: String::from("FATAL"),
 
-            log_format: log_format.clone(),
-            datetime_format: String::from("%Y-%m-%d %H:%M:%S"),
+            log_format: LogPattern::compile("[%h] %m")
+                .expect("The default log format is valid"),
+            datetime_format: DatetimePattern::compile("%Y-%m-%d %H:%M:%S")
+                .expect("The default datetime format is valid"),
+
+            debug_log_format: None,
+            info_log_format: None,
+            warning_log_format: None,
+            error_log_format: None,
+            fatal_log_format: None,
+
+            multiline_mode: MultilineMode::default(),
+            line_wrap: LineWrap::default(),
+            timezone: Timezone::default(),
+            timestamp_mode: TimestampMode::default(),
 
-            show_datetime: None,
+            timestamps: RuntimeState::default(),
         }
     }
 }
@@ -3,7 +3,10 @@ use std::sync::{
     RwLock
 };
 
-use crate::Logger;
+use crate::{
+    Error,
+    Logger
+};
 
 /// Global `Logger` struct that can be used with the `debug!`, `info!`, `warn!`,
 /// `err!`, and `fatal!` macros.
@@ -54,6 +57,62 @@ use crate::Logger;
 pub static LOGGER: LazyLock<RwLock<Logger>>
     = LazyLock::new(|| RwLock::new(Logger::default()));
 
+/// Shuts down the global `Logger` instance, writing the logs buffered by its
+/// outputs (see `Logger::shutdown`).
+///
+/// `LOGGER` is a static, so it is never dropped and its buffered logs are lost
+/// at exit unless this is called, either directly or through a
+/// `ShutdownGuard`.
+///
+/// # Examples
+///
+/// Shutting down the global logger at the end of `main`:
+/// ```
+/// # use prettylogger::{info, glob};
+/// info!("Exiting...");
+/// if let Err(errors) = glob::shutdown() {
+///     for error in errors {
+///         eprintln!("{error}");
+///     }
+/// }
+/// ```
+pub fn shutdown() -> Result<(), Vec<Error>> {
+    let logger = LOGGER.read().unwrap_or_else(|e| e.into_inner());
+    return logger.shutdown();
+}
+
+/// Shuts down the global `Logger` instance when dropped.
+///
+/// Keep it alive for as long as the global logger is used, usually by binding
+/// it at the start of `main`, so the buffered logs are written on return or
+/// panic. Write errors are handled according to the `OnErrorPolicy`; call
+/// `glob::shutdown` directly to inspect them instead.
+///
+/// > **Note**
+/// > `std::process::exit` doesn't run destructors, so the guard does nothing
+/// > if the process exits that way.
+///
+/// # Examples
+///
+/// Using a shutdown guard:
+/// ```
+/// # use prettylogger::{info, glob::ShutdownGuard};
+/// let _guard = ShutdownGuard::default();
+///
+/// info!("The global logger is shut down when `_guard` is dropped.");
+/// ```
+#[derive(Debug, Default)]
+#[must_use = "the global logger is shut down as soon as the guard is dropped"]
+pub struct ShutdownGuard {
+    _private: (),
+}
+
+impl Drop for ShutdownGuard {
+    fn drop(&mut self) {
+        let _ = shutdown();
+    }
+}
+
 
 /// Prints a debug message using the global `Logger` instance.
 ///
@@ -7,15 +7,28 @@ mod tests;
 #[doc = include_str!("../README.md")]
 mod fileio;
 mod json;
+mod pattern;
+mod runtime;
+mod writer;
 
+pub mod clock;
 pub mod colors;
 pub mod config;
 pub mod format;
 pub mod output;
+pub mod parse;
+pub mod query;
 pub mod glob;
 
-use std::sync::Mutex;
+use std::sync::{
+    Arc,
+    Mutex
+};
 
+use clock::{
+    Clock,
+    SystemClock
+};
 use format::LogFormatter;
 use serde::{
     Serialize,
@@ -158,6 +171,13 @@ pub struct Logger {
 
     pub(crate) verbosity: Verbosity,
     pub(crate) filtering_enabled: bool,
+
+    #[serde(skip, default = "default_clock")]
+    pub(crate) clock: Arc<dyn Clock>,
+}
+
+fn default_clock() -> Arc<dyn Clock> {
+    Arc::new(SystemClock)
 }
 
 impl Logger {
@@ -174,7 +194,7 @@ impl Logger {
         if self.filter_log(LogType::Debug) {
             return;
         }
-        let log = LogStruct::debug(message);
+        let log = LogStruct::new(message, LogType::Debug, self.clock.now());
         self.output.out(&log, &mut self.formatter.lock().unwrap());
     }
 
@@ -183,7 +203,7 @@ impl Logger {
         if self.filter_log(LogType::Info) {
             return;
         }
-        let log = LogStruct::info(message);
+        let log = LogStruct::new(message, LogType::Info, self.clock.now());
         self.output.out(&log, &mut self.formatter.lock().unwrap());
     }
 
@@ -192,19 +212,20 @@ impl Logger {
         if self.filter_log(LogType::Warning) {
             return;
         }
-        let log = LogStruct::warning(message);
+        let log = LogStruct::new(message, LogType::Warning, self.clock.now());
         self.output.out(&log, &mut self.formatter.lock().unwrap());
     }
 
     /// Prints an **error**.
     pub fn error(&self, message: &str) {
-        let log = LogStruct::error(message);
+        let log = LogStruct::new(message, LogType::Err, self.clock.now());
         self.output.out(&log, &mut self.formatter.lock().unwrap());
     }
 
     /// Prints a **fatal error**.
     pub fn fatal(&self, message: &str) {
-        let log = LogStruct::fatal_error(message);
+        let log = LogStruct::new(message, LogType::FatalError,
+            self.clock.now());
         self.output.out(&log, &mut self.formatter.lock().unwrap());
     }
 
@@ -222,6 +243,68 @@ impl Logger {
         self.verbosity = verbosity.into();
     }
 
+    /// Sets the clock used to timestamp logs.
+    ///
+    /// Also moves the point in time the formatter measures relative
+    /// timestamps from to the clock's current time.
+    ///
+    /// # Examples
+    ///
+    /// Making timestamps deterministic:
+    /// ```
+    /// # use std::sync::Arc;
+    /// # use prettylogger::{Logger, clock::ManualClock};
+    /// # use chrono::{Local, TimeZone};
+    /// let start = Local.with_ymd_and_hms(2025, 1, 1, 12, 0, 0).unwrap();
+    /// let mut logger = Logger::default();
+    /// logger.set_clock(Arc::new(ManualClock::new(start)));
+    /// ```
+    pub fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        self.formatter.lock().unwrap().set_timestamp_origin(clock.now());
+        self.clock = clock;
+    }
+
+    /// Writes the logs buffered by every output to their log files, and
+    /// waits until they have been written.
+    ///
+    /// Returns an error for every output that failed.
+    ///
+    /// # Examples
+    ///
+    /// Flushing the logs before a risky operation:
+    /// ```
+    /// # use prettylogger::Logger;
+    /// # let logger = Logger::default();
+    /// logger.info("About to do something risky...");
+    /// if let Err(errors) = logger.flush() {
+    ///     for error in errors {
+    ///         eprintln!("{error}");
+    ///     }
+    /// }
+    /// ```
+    pub fn flush(&self) -> Result<(), Vec<Error>> {
+        return self.output.flush();
+    }
+
+    /// Writes the logs buffered by every output according to their
+    /// `OnDropPolicy`, and closes the log files. This is what happens when the
+    /// `Logger` is dropped.
+    ///
+    /// Afterwards, logs are only printed to `stderr` and stored in the log
+    /// buffer.
+    ///
+    /// Returns an error for every output that failed or discarded logs.
+    ///
+    /// # Examples
+    /// ```
+    /// # use prettylogger::Logger;
+    /// # let logger = Logger::default();
+    /// logger.shutdown().expect("Failed to shut down the logger!");
+    /// ```
+    pub fn shutdown(&self) -> Result<(), Vec<Error>> {
+        return self.output.shutdown();
+    }
+
     /// Enables log filtering.
     pub fn enable_log_filtering(&mut self) {
         self.filtering_enabled = true;
@@ -242,13 +325,15 @@ impl Default for Logger {
             filtering_enabled: true,
 
             formatter: LogFormatter::default().into(),
+
+            clock: default_clock(),
         }
     }
 }
 
 impl Drop for Logger {
     fn drop(&mut self) {
-        self.output.file_output.lock().unwrap().drop_flush();
+        let _ = self.output.shutdown();
     }
 }
 
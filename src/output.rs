@@ -4,8 +4,31 @@
 /// Provides log stream implementations for directing log output to various
 /// destinations, such as files, standard error, or a log buffer.
 use std::{
-    fs::OpenOptions,
-    sync::Mutex
+    collections::VecDeque,
+    fmt::{
+        Debug,
+        Formatter
+    },
+    fs::{
+        File,
+        OpenOptions
+    },
+    io::{
+        BufWriter,
+        Write
+    },
+    time::{
+        Duration,
+        Instant
+    },
+    sync::{
+        Mutex,
+        atomic::{
+            AtomicBool,
+            AtomicUsize,
+            Ordering
+        }
+    },
 };
 
 use serde::{
@@ -16,14 +39,31 @@ use serde::{
 use crate::{
     Error,
     config::{
+        BackpressurePolicy,
+        FailoverTarget,
+        FileLockMode,
         LogStruct,
-        OnDropPolicy
+        LogType,
+        OnDropPolicy,
+        OnErrorPolicy,
+        SyncPolicy,
+        WriteMode
     },
     format::LogFormatter,
+    query::LogQuery,
     fileio::{
-        append_to_file,
+        LogFile,
+        REOPEN_GENERATION,
+        WriteOptions,
+        append_with_handle,
+        create_or_append_to_file,
         overwrite_file
     },
+    runtime::RuntimeState,
+    writer::{
+        AsyncWriter,
+        QueueConfig
+    },
 };
 
 /// Common trait for toggleable objects.
@@ -36,8 +76,8 @@ pub trait Toggleable {
     fn is_enabled(&self) -> &bool;
 }
 
-/// Wraps `StderrStream`, `BufferStream` and `FileStream` in one object used
-/// internally by `Logger`.
+/// Wraps `StderrStream`, `BufferStream`, `FileStream` and `FailoverStream` in
+/// one object used internally by `Logger`.
 ///
 /// # Examples
 ///
@@ -65,10 +105,31 @@ pub struct LogOutput {
     pub file_output: Mutex<FileStream>,
     /// Buffer stream for storing log messages.
     pub buffer_output: Mutex<BufferStream>,
+    /// File output stream that diverts logs elsewhere while its log file
+    /// can't be written to.
+    #[serde(default)]
+    pub failover_output: Mutex<FailoverStream>,
 
     enabled: bool,
+    #[serde(default)]
+    on_error_policy: OnErrorPolicy,
+
+    #[serde(skip)]
+    error_callback: Option<ErrorCallback>,
+    #[serde(skip)]
+    error_reported: AtomicBool,
+    #[serde(skip)]
+    failed_writes: AtomicUsize,
+    #[serde(skip)]
+    failed_logs: AtomicUsize,
 }
 
+/// Signature of callbacks set with `LogOutput::set_error_callback`.
+type ErrorCallbackFn = dyn Fn(&Error, &str) + Send + Sync;
+
+/// A user callback invoked by `LogOutput` when writing logs fails.
+struct ErrorCallback(Box<ErrorCallbackFn>);
+
 /// Used for printing logs to `stderr`.
 ///
 /// # Examples
@@ -134,14 +195,112 @@ pub struct StderrStream {
 pub struct FileStream {
     enabled: bool,
     max_buffer_size: Option<usize>,
+    #[serde(default)]
+    max_buffer_bytes: Option<usize>,
     on_drop_policy: OnDropPolicy,
+    #[serde(default)]
+    sync_policy: SyncPolicy,
+    #[serde(default)]
+    write_mode: WriteMode,
+    #[serde(default)]
+    backpressure_policy: BackpressurePolicy,
+    #[serde(default = "default_queue_capacity")]
+    queue_capacity: usize,
+    #[serde(default)]
+    flush_interval_ms: Option<u64>,
+    #[serde(default = "default_flush_on_error")]
+    flush_on_error: bool,
+    #[serde(default)]
+    detect_rotation: bool,
+    #[serde(default)]
+    file_lock_mode: FileLockMode,
 
     #[serde(skip)]
     lock_enabled: bool,
     #[serde(skip)]
     log_file_path: String,
     #[serde(skip)]
-    log_buffer: Vec<String>,
+    log_buffer: String,
+    #[serde(skip)]
+    buffered_logs: usize,
+    #[serde(skip)]
+    buffered_errors: bool,
+    #[serde(skip)]
+    failed_batch: String,
+    #[serde(skip)]
+    failed_batch_logs: usize,
+    #[serde(skip)]
+    dropped_logs: usize,
+    #[serde(skip)]
+    log_file: RuntimeState<Handle<LogFile>>,
+    #[serde(skip)]
+    writer: RuntimeState<Handle<AsyncWriter>>,
+}
+
+/// Holds an open log file or writer thread of a `FileStream`, if it has one.
+///
+/// A cloned stream opens its own when needed.
+struct Handle<T>(Option<T>);
+
+fn default_queue_capacity() -> usize {
+    1024
+}
+
+fn default_flush_on_error() -> bool {
+    true
+}
+
+/// What happened to the logs left in a `FileStream` buffer when it was shut
+/// down, as returned by `FileStream::shutdown`.
+///
+/// Each variant holds the number of logs it applies to.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ShutdownOutcome {
+    /// The buffer was empty, so there was nothing to write
+    Nothing,
+    /// The logs were written to the log file
+    Written(usize),
+    /// The logs were appended to the `OnDropPolicy::FallbackFile` path
+    WrittenToFallback(usize),
+    /// The logs were printed to `stderr`
+    DumpedToStderr(usize),
+    /// The log file was locked, so the logs were discarded
+    Discarded(usize),
+}
+
+/// How `BufferStream` renders logs when exporting them.
+///
+/// # Examples
+///
+/// Exporting logs formatted like the other outputs format them:
+/// ```
+/// # use prettylogger::{
+/// #     output::{BufferStream, ExportFormat},
+/// #     format::LogFormatter,
+/// # };
+/// # let buffer_output = BufferStream::default();
+/// let mut formatter = LogFormatter::default();
+/// let mut report = Vec::new();
+/// buffer_output.export(&mut report, ExportFormat::Text(&mut formatter))
+///     .expect("Failed to export the logs!");
+/// ```
+#[derive(Debug)]
+pub enum ExportFormat<'a> {
+    /// Format each log with the given formatter
+    Text(&'a mut LogFormatter),
+    /// Serialize each log as a JSON object on its own line
+    JsonLines,
+}
+
+impl ShutdownOutcome {
+    /// Returns an error describing the lost logs if they were discarded.
+    fn discarded_error(self) -> Option<Error> {
+        match self {
+            ShutdownOutcome::Discarded(logs) => Some(Error::new(&format!(
+                "Discarded {logs} logs because the log file is locked!"))),
+            _ => None,
+        }
+    }
 }
 
 /// Used for storing logs in a buffer for later use.
@@ -163,35 +322,172 @@ pub struct FileStream {
 /// // Obtain a reference to the log buffer
 /// let buffer = buffer_output.get_log_buffer();
 /// ````
+///
+/// Keeping only the most recent logs:
+/// ```
+/// # use prettylogger::{
+/// #     output::{BufferStream, Toggleable},
+/// #     config::LogStruct,
+/// # };
+/// let mut buffer_output = BufferStream::default();
+/// buffer_output.enable();
+/// buffer_output.set_capacity(2);
+///
+/// for i in 0..3 {
+///     buffer_output.out(&LogStruct::debug(&format!("Log number {i}")));
+/// }
+///
+/// // The oldest log was evicted
+/// assert_eq!(buffer_output.get_log_buffer()[0].message, "Log number 1");
+/// assert_eq!(buffer_output.evicted_logs(), 1);
+/// ```
 #[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Serialize,
     Deserialize, Default)]
 pub struct BufferStream {
     enabled: bool,
+    #[serde(default)]
+    capacity: Option<usize>,
+    #[serde(default)]
+    byte_capacity: Option<usize>,
+
+    #[serde(skip)]
+    pub(crate) log_buffer: VecDeque<LogStruct>,
+    #[serde(skip)]
+    buffered_bytes: usize,
+    #[serde(skip)]
+    evicted_logs: usize,
+}
+
+/// Wraps a primary `FileStream`, diverting logs to a `FailoverTarget` while
+/// the primary log file can't be written to.
+///
+/// After a failed write, the primary is retried with exponential backoff:
+/// logs arriving before the retry delay has passed go straight to the
+/// fallback, and the next log after it is written (and flushed) to the
+/// primary to check whether it has recovered. Diverted logs are recorded
+/// until the record is cleared, keeping the last 1024 by default.
+///
+/// # Examples
+///
+/// Falling back to an alternate log file:
+/// ```
+/// # use prettylogger::{
+/// #     output::{FailoverStream, Toggleable},
+/// #     format::LogFormatter,
+/// #     config::{LogStruct, FailoverTarget},
+/// # };
+/// # let mut path = std::env::temp_dir();
+/// # path.push("libprettylogger-tests/failover-struct-doc.log");
+/// # let path = &path.to_str().unwrap().to_string();
+/// # let mut fallback_path = std::env::temp_dir();
+/// # fallback_path.push("libprettylogger-tests/failover-struct-doc-fb.log");
+/// # let fallback_path = fallback_path.to_str().unwrap().to_string();
+/// let mut formatter = LogFormatter::default();
+/// let mut failover_output = FailoverStream::default();
+///
+/// // Set up the primary log file
+/// failover_output.primary.set_log_file_path(&path)
+///     .expect("Failed to set the log file path!");
+/// failover_output.primary.enable()
+///     .expect("Failed to enable the primary output!");
+///
+/// // Set up the fallback and enable the stream
+/// failover_output.set_fallback(FailoverTarget::File(fallback_path));
+/// failover_output.enable();
+///
+/// failover_output.out(&LogStruct::debug("Hello from file!"), &mut formatter)
+///     .expect("Failed to write to the buffer!");
+///
+/// // Logs that ended up in the fallback
+/// let diverted = failover_output.get_diverted_logs();
+/// ```
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Serialize,
+    Deserialize)]
+pub struct FailoverStream {
+    /// The primary file stream.
+    pub primary: FileStream,
+
+    enabled: bool,
+    fallback: FailoverTarget,
+    initial_retry_delay_ms: u64,
+    max_retry_delay_ms: u64,
+    #[serde(default = "default_diverted_capacity")]
+    diverted_capacity: Option<usize>,
 
     #[serde(skip)]
-    pub(crate) log_buffer: Vec<LogStruct>,
+    pending_logs: VecDeque<LogStruct>,
+    #[serde(skip)]
+    evicted_pending_logs: usize,
+    #[serde(skip)]
+    diverted_logs: VecDeque<LogStruct>,
+    #[serde(skip)]
+    evicted_diverted_logs: usize,
+    #[serde(skip)]
+    retry: Option<RetryState>,
+}
+
+fn default_diverted_capacity() -> Option<usize> {
+    Some(1024)
+}
+
+/// Pushes `new_logs` to the back of `logs`, evicting the oldest logs over
+/// `capacity`. Returns how many logs were evicted.
+fn push_bounded<I: IntoIterator<Item = LogStruct>>(
+logs: &mut VecDeque<LogStruct>, new_logs: I, capacity: Option<usize>)
+-> usize {
+    logs.extend(new_logs);
+    let excess = capacity.map_or(0, |max| logs.len().saturating_sub(max));
+    logs.drain(..excess);
+    return excess;
+}
+
+/// Backoff state of a failing `FailoverStream` primary.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+struct RetryState {
+    delay: Duration,
+    retry_at: Instant,
+}
+
+impl Drop for FailoverStream {
+    fn drop(&mut self) {
+        let _ = self.shutdown();
+    }
 }
 
 impl Drop for FileStream {
     fn drop(&mut self) {
-        let _ = self.internal_flush(true);
+        self.drop_flush();
     }
 }
 
 impl PartialEq for LogOutput {
     fn eq(&self, other: &Self) -> bool {
         return self.enabled == other.enabled &&
+            self.on_error_policy == other.on_error_policy &&
             self.stderr_output == other.stderr_output;
     }
 }
 
+impl Debug for ErrorCallback {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "ErrorCallback")
+    }
+}
+
 impl Default for LogOutput {
     fn default() -> Self {
         LogOutput {
             enabled: true,
+            on_error_policy: OnErrorPolicy::default(),
             stderr_output: StderrStream::default(),
             file_output: FileStream::default().into(),
             buffer_output: BufferStream::default().into(),
+            failover_output: FailoverStream::default().into(),
+
+            error_callback: None,
+            error_reported: AtomicBool::new(false),
+            failed_writes: AtomicUsize::new(0),
+            failed_logs: AtomicUsize::new(0),
         }
     }
 }
@@ -209,11 +505,68 @@ impl Default for FileStream {
         FileStream {
             enabled: false,
             max_buffer_size: Some(128),
+            max_buffer_bytes: None,
             on_drop_policy: OnDropPolicy::default(),
+            sync_policy: SyncPolicy::default(),
+            write_mode: WriteMode::default(),
+            backpressure_policy: BackpressurePolicy::default(),
+            queue_capacity: default_queue_capacity(),
+            flush_interval_ms: None,
+            flush_on_error: default_flush_on_error(),
+            detect_rotation: false,
+            file_lock_mode: FileLockMode::default(),
 
             lock_enabled: false,
             log_file_path: String::from(""),
-            log_buffer: Vec::new(),
+            log_buffer: String::new(),
+            buffered_logs: 0,
+            buffered_errors: false,
+            failed_batch: String::new(),
+            failed_batch_logs: 0,
+            dropped_logs: 0,
+            log_file: RuntimeState::default(),
+            writer: RuntimeState::default(),
+        }
+    }
+}
+
+impl<T> Default for Handle<T> {
+    fn default() -> Self {
+        Handle(None)
+    }
+}
+
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Self {
+        Handle(None)
+    }
+}
+
+impl<T> Debug for Handle<T> {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self.0 {
+            Some(_) => write!(f, "Handle(Open)"),
+            None => write!(f, "Handle(None)"),
+        }
+    }
+}
+
+impl Default for FailoverStream {
+    fn default() -> Self {
+        FailoverStream {
+            primary: FileStream::default(),
+
+            enabled: false,
+            fallback: FailoverTarget::default(),
+            initial_retry_delay_ms: 1000,
+            max_retry_delay_ms: 60000,
+            diverted_capacity: default_diverted_capacity(),
+
+            pending_logs: VecDeque::new(),
+            evicted_pending_logs: 0,
+            diverted_logs: VecDeque::new(),
+            evicted_diverted_logs: 0,
+            retry: None,
         }
     }
 }
@@ -252,6 +605,25 @@ impl Toggleable for StderrStream {
     }
 }
 
+impl Toggleable for FailoverStream {
+    /// Enables the output.
+    ///
+    /// The primary `FileStream` has to be enabled separately.
+    fn enable(&mut self) {
+        self.enabled = true;
+    }
+
+    /// Disables the output.
+    fn disable(&mut self) {
+        self.enabled = false;
+    }
+
+    /// Returns whether the output is enabled.
+    fn is_enabled(&self) -> &bool {
+        return &self.enabled;
+    }
+}
+
 impl Toggleable for BufferStream {
     /// Enables the output.
     fn enable(&mut self) {
@@ -271,50 +643,379 @@ impl Toggleable for BufferStream {
 
 impl LogOutput {
     /// Passes the log and its formatter to child streams for processing.
+    ///
+    /// Logs that could not be written to the log file are handled according
+    /// to the `OnErrorPolicy`.
     pub fn out(&self, log: &LogStruct, formatter: &mut LogFormatter) {
         if self.enabled {
+            formatter.begin_log(log);
             self.stderr_output.out(log, formatter);
-            let _ = self.file_output.lock().unwrap().out(log, formatter);
+
+            let failed_write = {
+                let mut file_output = self.file_output.lock().unwrap();
+                if *file_output.is_enabled() {
+                    file_output.out(log, formatter).err()
+                        .map(|e| (e, file_output.take_failed_batch()))
+                }
+                else {
+                    None
+                }
+            };
+            if let Some((e, (batch, logs))) = failed_write {
+                self.handle_write_error(&e, &batch, logs);
+            }
+
+            let mut failover_output = self.failover_output.lock().unwrap();
+            if *failover_output.is_enabled() {
+                let _ = failover_output.out(log, formatter);
+            }
+            drop(failover_output);
+            formatter.end_log();
+
             self.buffer_output.lock().unwrap().out(log);
         }
     }
+
+    /// Writes the logs buffered by the file and failover outputs to their log
+    /// files, and waits until the writer threads have written every queued
+    /// log.
+    ///
+    /// Returns an error for every output that failed. Logs that could not be
+    /// written to the log file are handled according to the `OnErrorPolicy`.
+    ///
+    /// # Examples
+    /// ```
+    /// # use prettylogger::output::LogOutput;
+    /// let log_output = LogOutput::default();
+    ///
+    /// if let Err(errors) = log_output.flush() {
+    ///     for error in errors {
+    ///         eprintln!("{error}");
+    ///     }
+    /// }
+    /// ```
+    pub fn flush(&self) -> Result<(), Vec<Error>> {
+        let mut errors = Vec::new();
+
+        let failed_write = {
+            let mut file_output = self.file_output.lock().unwrap();
+            file_output.flush_pending().err()
+                .map(|e| (e, file_output.take_failed_batch()))
+        };
+        if let Some((e, (batch, logs))) = failed_write {
+            if logs > 0 {
+                self.handle_write_error(&e, &batch, logs);
+            }
+            errors.push(Error::new(&format!("File output: {e}")));
+        }
+
+        let mut failover_output = self.failover_output.lock().unwrap();
+        if let Err(e) = failover_output.flush_pending() {
+            errors.push(Error::new(&format!("Failover output: {e}")));
+        }
+        drop(failover_output);
+
+        return match errors.is_empty() {
+            true => Ok(()),
+            false => Err(errors),
+        };
+    }
+
+    /// Shuts down the file and failover outputs, writing the logs left in
+    /// their buffers according to their `OnDropPolicy` (see
+    /// `FileStream::shutdown`). Afterwards, logs are no longer written to
+    /// log files.
+    ///
+    /// Returns an error for every output that failed or discarded logs. Logs
+    /// that could not be written to the log file are handled according to the
+    /// `OnErrorPolicy`.
+    pub fn shutdown(&self) -> Result<(), Vec<Error>> {
+        let mut errors = Vec::new();
+
+        let result = {
+            let mut file_output = self.file_output.lock().unwrap();
+            file_output.shutdown()
+                .map_err(|e| (e, file_output.take_failed_batch()))
+        };
+        match result {
+            Ok(outcome) => {
+                if let Some(e) = outcome.discarded_error() {
+                    errors.push(Error::new(&format!("File output: {e}")));
+                }
+            },
+            Err((e, (batch, logs))) => {
+                if logs > 0 {
+                    self.handle_write_error(&e, &batch, logs);
+                }
+                errors.push(Error::new(&format!("File output: {e}")));
+            },
+        }
+
+        let result = self.failover_output.lock().unwrap().shutdown();
+        let error = match result {
+            Ok(outcome) => outcome.discarded_error(),
+            Err(e) => Some(e),
+        };
+        if let Some(e) = error {
+            errors.push(Error::new(&format!("Failover output: {e}")));
+        }
+
+        return match errors.is_empty() {
+            true => Ok(()),
+            false => Err(errors),
+        };
+    }
+
+    /// Counts a failed write and applies the `OnErrorPolicy` to the logs
+    /// that were not written.
+    fn handle_write_error(&self, error: &Error, batch: &str, logs: usize) {
+        self.failed_writes.fetch_add(1, Ordering::Relaxed);
+        self.failed_logs.fetch_add(logs, Ordering::Relaxed);
+
+        match self.on_error_policy {
+            OnErrorPolicy::Ignore => (),
+            OnErrorPolicy::ReportOnce => {
+                if !self.error_reported.swap(true, Ordering::Relaxed) {
+                    eprintln!("Failed to write logs to the log file: {}. \
+                        Further errors will not be reported.", error);
+                }
+            },
+            OnErrorPolicy::Callback => {
+                if let Some(callback) = &self.error_callback {
+                    (callback.0)(error, batch);
+                }
+            },
+            OnErrorPolicy::FailoverToStderr => {
+                if !self.stderr_output.is_enabled() {
+                    eprint!("{}", batch);
+                }
+            },
+        }
+    }
+
+    /// Sets the policy for handling logs that could not be written to the log
+    /// file.
+    pub fn set_on_error_policy<I: Into<OnErrorPolicy>>(&mut self, policy: I) {
+        self.on_error_policy = policy.into();
+    }
+
+    /// Sets a callback invoked with the error and the formatted logs every
+    /// time writing to the log file fails, and switches the `OnErrorPolicy`
+    /// to `Callback`.
+    ///
+    /// Callbacks are not part of templates.
+    ///
+    /// # Examples
+    /// ```
+    /// # use prettylogger::output::LogOutput;
+    /// let mut log_output = LogOutput::default();
+    ///
+    /// log_output.set_error_callback(|error, logs| {
+    ///     eprintln!("Lost some logs ({error}):\n{logs}");
+    /// });
+    /// ```
+    pub fn set_error_callback<F>(&mut self, callback: F)
+    where F: Fn(&Error, &str) + Send + Sync + 'static {
+        self.error_callback = Some(ErrorCallback(Box::new(callback)));
+        self.on_error_policy = OnErrorPolicy::Callback;
+    }
+
+    /// Returns how many times writing to the log file has failed.
+    pub fn failed_writes(&self) -> usize {
+        return self.failed_writes.load(Ordering::Relaxed);
+    }
+
+    /// Returns how many logs were in the writes that failed.
+    pub fn failed_logs(&self) -> usize {
+        return self.failed_logs.load(Ordering::Relaxed);
+    }
+
+    /// Resets the failed write counters, and lets `OnErrorPolicy::ReportOnce`
+    /// report the next error.
+    pub fn reset_failed_writes(&self) {
+        self.failed_writes.store(0, Ordering::Relaxed);
+        self.failed_logs.store(0, Ordering::Relaxed);
+        self.error_reported.store(false, Ordering::Relaxed);
+    }
 }
 
 impl StderrStream {
     /// Formats the given log using a formatter and prints it to `stderr`.
     pub fn out(self, log: &LogStruct, formatter: &mut LogFormatter) {
         if self.enabled {
-            eprint!("{}", formatter.format_log(log));
+            eprint!("{}", formatter.format_log_for_terminal(log));
         }
     }
 }
 
 impl FileStream {
-    fn push_to_buffer(&mut self, log: String) -> Result<(), Error> {
+    fn push_to_buffer(&mut self, log: &LogStruct,
+    formatter: &mut LogFormatter) -> Result<(), Error> {
         if !self.enabled {
             return Err(Error::new("Output disabled!"));
         }
 
-        self.log_buffer.push(log);
+        let is_error = matches!(log.log_type,
+            LogType::Err | LogType::FatalError);
+        formatter.format_log_into(log, &mut self.log_buffer);
+        self.buffered_logs += 1;
+        self.buffered_errors |= is_error;
 
-        match self.max_buffer_size {
-            Some(size) => {
-                if self.log_buffer.len() >= size {
-                    return self.internal_flush(false);
-                }
-                else {
-                    return Ok(());
-                }
-            },
-            None => Ok(()),
+        // The buffer keeps growing while the log file is locked
+        if self.lock_enabled {
+            return Ok(());
+        }
+
+        if self.flush_interval_ms.is_some() {
+            return self.send_to_writer(!(is_error && self.flush_on_error));
+        }
+        if is_error && self.flush_on_error {
+            return self.internal_flush(false);
+        }
+
+        let is_full = self.max_buffer_size
+            .is_some_and(|size| self.buffered_logs >= size) ||
+            self.max_buffer_bytes
+            .is_some_and(|bytes| self.log_buffer.len() >= bytes);
+        if is_full {
+            return self.internal_flush(false);
+        }
+        else {
+            return Ok(());
+        }
+    }
+
+    /// Returns whether writing the log buffer has to be synced to the disk.
+    fn buffer_needs_sync(&self) -> bool {
+        match self.sync_policy {
+            SyncPolicy::Never => false,
+            SyncPolicy::EveryFlush => true,
+            SyncPolicy::OnError => self.buffered_errors,
         }
     }
 
     /// Write contents of the log buffer to the log file and clear the buffer.
+    ///
+    /// When writing fails, the logs are kept aside until the next failure so
+    /// `LogOutput` can handle them.
     fn append_to_log_file(&mut self) -> Result<(), Error> {
-        let buf = self.log_buffer.join("");
-        self.log_buffer = Vec::new();
-        return append_to_file(&self.log_file_path, &buf);
+        if self.uses_writer() {
+            return self.send_to_writer(false);
+        }
+        return self.write_log_buffer(None);
+    }
+
+    /// Writes the log buffer to the log file on the calling thread, waiting
+    /// at most `lock_timeout` for the advisory lock if one is given.
+    fn write_log_buffer(&mut self, lock_timeout: Option<Duration>)
+    -> Result<(), Error> {
+        let options = WriteOptions {
+            sync_data: self.buffer_needs_sync(),
+            detect_rotation: self.detect_rotation,
+            lock_file: self.file_lock_mode == FileLockMode::Advisory ||
+                lock_timeout.is_some(),
+            lock_timeout,
+        };
+        let result = append_with_handle(&mut self.log_file.0,
+            &self.log_file_path, &self.log_buffer, options);
+        if result.is_err() {
+            std::mem::swap(&mut self.failed_batch, &mut self.log_buffer);
+            self.failed_batch_logs = self.buffered_logs;
+        }
+        self.log_buffer.clear();
+        self.buffered_logs = 0;
+        self.buffered_errors = false;
+        return result;
+    }
+
+    /// Returns whether flushed logs go through the writer thread, which is
+    /// the case in `WriteMode::Asynchronous` or when a flush interval is set.
+    fn uses_writer(&self) -> bool {
+        return self.write_mode == WriteMode::Asynchronous ||
+            self.flush_interval_ms.is_some();
+    }
+
+    /// Returns the current writer thread queue settings.
+    fn queue_config(&self) -> QueueConfig {
+        QueueConfig {
+            capacity: self.queue_capacity,
+            backpressure_policy: self.backpressure_policy,
+            flush_interval: self.flush_interval_ms.map(Duration::from_millis),
+            max_held_logs: self.max_buffer_size,
+            max_held_bytes: self.max_buffer_bytes,
+            detect_rotation: self.detect_rotation,
+            lock_file: self.file_lock_mode == FileLockMode::Advisory,
+        }
+    }
+
+    /// Moves the contents of the log buffer to the writer thread's queue,
+    /// starting the thread if needed. With `hold` set, the logs are written
+    /// once the flush interval passes instead of right away.
+    ///
+    /// Returns an error if the thread can't be started, or if a write made by
+    /// the thread failed since the last check.
+    fn send_to_writer(&mut self, hold: bool) -> Result<(), Error> {
+        let config = self.queue_config();
+        if self.writer.0.is_none() {
+            self.writer.0 = Some(
+                AsyncWriter::spawn(&self.log_file_path, config)?);
+        }
+        let writer = self.writer.0.as_ref().expect("Writer was started");
+
+        let sync = self.buffer_needs_sync();
+        let text = std::mem::take(&mut self.log_buffer);
+        let logs = std::mem::take(&mut self.buffered_logs);
+        self.buffered_errors = false;
+        self.dropped_logs += writer.send(text, logs, hold, sync, config);
+
+        return self.take_writer_error();
+    }
+
+    /// Returns the error of the last failed background write, keeping its
+    /// logs aside like a failed synchronous write.
+    fn take_writer_error(&mut self) -> Result<(), Error> {
+        let failed = match &self.writer.0 {
+            Some(writer) => writer.take_error(),
+            None => None,
+        };
+        match failed {
+            Some((e, (text, logs))) => {
+                self.failed_batch = text;
+                self.failed_batch_logs = logs;
+                return Err(e);
+            },
+            None => Ok(()),
+        }
+    }
+
+    /// Stops the writer thread after it has written every queued log.
+    fn stop_writer(&mut self) {
+        if let Some(writer) = self.writer.0.take() {
+            drop(writer);
+        }
+    }
+
+    /// Stops the writer thread after it has written every queued log, moving
+    /// the logs of its failed writes back to the front of the log buffer.
+    fn stop_writer_and_requeue(&mut self) {
+        let writer = match self.writer.0.take() {
+            Some(writer) => writer,
+            None => return,
+        };
+        writer.wait_until_written();
+        let failed = writer.take_error();
+        drop(writer);
+
+        if let Some((_, (text, logs))) = failed {
+            self.log_buffer.insert_str(0, &text);
+            self.buffered_logs += logs;
+        }
+    }
+
+    /// Returns the logs from the last failed write along with their count.
+    pub(crate) fn take_failed_batch(&mut self) -> (String, usize) {
+        let logs = std::mem::take(&mut self.failed_batch_logs);
+        return (std::mem::take(&mut self.failed_batch), logs);
     }
 
     /// Handle flushing logic internally.
@@ -328,19 +1029,14 @@ impl FileStream {
         }
 
         if is_drop_flush {
-            if self.lock_enabled {
-                if self.on_drop_policy == OnDropPolicy::IgnoreLogFileLock {
-                    return self.append_to_log_file();
-                }
-                else {
-                    return Err(Error::new(
-                        &format!("Lock is enabled and on drop policy se to '{}'!",
-                        self.on_drop_policy)));
-                }
-            }
-            else {
-                return self.append_to_log_file();
-            }
+            return match self.flush_on_drop()? {
+                ShutdownOutcome::Nothing =>
+                    Err(Error::new("Log buffer is empty!")),
+                ShutdownOutcome::Discarded(_) => Err(Error::new(
+                    &format!("Lock is enabled and on drop policy se to '{}'!",
+                    self.on_drop_policy))),
+                _ => Ok(()),
+            };
         }
 
         if self.lock_enabled {
@@ -351,9 +1047,84 @@ impl FileStream {
         }
     }
 
-    pub(crate) fn drop_flush(&mut self) {
-        let _ = self.internal_flush(true);
-    }
+    /// Writes the log buffer according to the `OnDropPolicy`, after the
+    /// writer thread has written every queued log.
+    fn flush_on_drop(&mut self) -> Result<ShutdownOutcome, Error> {
+        self.stop_writer_and_requeue();
+        if self.log_buffer.is_empty() {
+            return Ok(ShutdownOutcome::Nothing);
+        }
+
+        let logs = self.buffered_logs;
+        let result = match self.on_drop_policy.clone() {
+            OnDropPolicy::WaitForLock { timeout_ms } => self.write_log_buffer(
+                Some(Duration::from_millis(timeout_ms))),
+            _ if !self.lock_enabled => self.write_log_buffer(None),
+            OnDropPolicy::IgnoreLogFileLock => self.write_log_buffer(None),
+            OnDropPolicy::DiscardLogBuffer =>
+                return Ok(ShutdownOutcome::Discarded(logs)),
+            OnDropPolicy::FallbackFile(_) | OnDropPolicy::DumpToStderr => {
+                let text = std::mem::take(&mut self.log_buffer);
+                self.buffered_logs = 0;
+                self.buffered_errors = false;
+                return self.divert_on_drop(text, logs)
+                    .expect("Policy diverts logs");
+            },
+        };
+
+        match result {
+            Ok(()) => Ok(ShutdownOutcome::Written(logs)),
+            Err(e) => {
+                let (text, logs) = self.take_failed_batch();
+                match self.divert_on_drop(text, logs) {
+                    Some(outcome) => outcome,
+                    None => Err(e),
+                }
+            },
+        }
+    }
+
+    /// Sends logs that can't be written to the log file where the
+    /// `OnDropPolicy` says, or returns `None` if it doesn't say.
+    fn divert_on_drop(&mut self, text: String, logs: usize)
+    -> Option<Result<ShutdownOutcome, Error>> {
+        match &self.on_drop_policy {
+            OnDropPolicy::FallbackFile(path) => Some(
+                create_or_append_to_file(path, &text)
+                    .map(|_| ShutdownOutcome::WrittenToFallback(logs))),
+            OnDropPolicy::DumpToStderr => {
+                eprint!("{text}");
+                Some(Ok(ShutdownOutcome::DumpedToStderr(logs)))
+            },
+            _ => {
+                self.failed_batch = text;
+                self.failed_batch_logs = logs;
+                None
+            },
+        }
+    }
+
+    /// Flushes the log buffer if it holds any logs and waits for the writer
+    /// thread to write every queued log. Does nothing when disabled.
+    pub(crate) fn flush_pending(&mut self) -> Result<(), Error> {
+        if !self.enabled {
+            return Ok(());
+        }
+        if !self.log_buffer.is_empty() {
+            return self.flush();
+        }
+        if let Some(writer) = &self.writer.0 {
+            writer.wait_until_written();
+            return self.take_writer_error();
+        }
+        return Ok(());
+    }
+
+    /// Flushes the log buffer according to the `OnDropPolicy` and waits for
+    /// the writer thread to write every queued log.
+    pub(crate) fn drop_flush(&mut self) {
+        let _ = self.shutdown();
+    }
 
     /// Sets the log file path.
     ///
@@ -379,6 +1150,9 @@ impl FileStream {
     ///     .expect("Failed to enable the output!");
     /// ```
     pub fn set_log_file_path(&mut self, path: &str) -> Result<(), Error> {
+        // Logs queued for the old log file are written to it first
+        self.stop_writer();
+        self.log_file.0 = None;
         match OpenOptions::new().write(true).create(true).truncate(true).open(path) {
             Ok(_) => {
                 self.log_file_path = path.to_string();
@@ -428,7 +1202,7 @@ impl FileStream {
     /// ```
     pub fn out(&mut self, log: &LogStruct, formatter: &mut LogFormatter)
         -> Result<(), Error> {
-        return self.push_to_buffer(formatter.format_log(log));
+        return self.push_to_buffer(log, formatter);
     }
 
     /// Flush the contents of the log buffer to the log file.
@@ -460,8 +1234,76 @@ impl FileStream {
     /// // Write the log buffer contents to the log file
     /// file_output.flush();
     /// ```
+    ///
+    /// In `WriteMode::Asynchronous` or with a flush interval, this blocks
+    /// until the writer thread has written every queued log, and returns an
+    /// error if any of its writes failed. An empty log buffer is not an error
+    /// then, since its logs were already handed to the writer thread.
     pub fn flush(&mut self) -> Result<(), Error> {
-        return self.internal_flush(false);
+        let was_empty = self.log_buffer.is_empty();
+        let result = self.internal_flush(false);
+        if let Some(writer) = &self.writer.0 {
+            writer.wait_until_written();
+            self.take_writer_error()?;
+            if was_empty && self.enabled {
+                return Ok(());
+            }
+        }
+        return result;
+    }
+
+    /// Writes the logs left in the buffer according to the `OnDropPolicy`,
+    /// closes the log file and disables the stream.
+    ///
+    /// This is what happens when a `FileStream` is dropped, but calling it
+    /// explicitly tells what happened to the logs. Logs queued for the writer
+    /// thread are written first, and the logs of its failed writes are handled
+    /// along with the buffer.
+    ///
+    /// # Examples
+    /// ```
+    /// # use prettylogger::{
+    /// #     output::{FileStream, ShutdownOutcome},
+    /// #     format::LogFormatter,
+    /// #     config::LogStruct,
+    /// # };
+    /// # let mut path = std::env::temp_dir();
+    /// # path.push("libprettylogger-tests/fo-shutdown-doc.log");
+    /// # let path = &path.to_str().unwrap().to_string();
+    /// # let mut formatter = LogFormatter::default();
+    /// let mut file_output = FileStream::default();
+    /// file_output.set_log_file_path(&path)
+    ///     .expect("Failed to set the log file path!");
+    /// file_output.enable()
+    ///     .expect("Failed to enable the output!");
+    ///
+    /// file_output.out(&LogStruct::debug("Hello from file!"), &mut formatter)
+    ///     .expect("Failed to write to the buffer!");
+    ///
+    /// let outcome = file_output.shutdown()
+    ///     .expect("Failed to shut down the output!");
+    /// assert_eq!(outcome, ShutdownOutcome::Written(1));
+    /// ```
+    pub fn shutdown(&mut self) -> Result<ShutdownOutcome, Error> {
+        let result = if self.enabled {
+            self.flush_on_drop()
+        }
+        else {
+            self.stop_writer();
+            match self.buffered_logs {
+                0 => Ok(ShutdownOutcome::Nothing),
+                logs => Ok(ShutdownOutcome::Discarded(logs)),
+            }
+        };
+
+        if let Ok(ShutdownOutcome::Discarded(_)) = result {
+            self.log_buffer.clear();
+            self.buffered_logs = 0;
+            self.buffered_errors = false;
+        }
+        self.log_file.0 = None;
+        self.enabled = false;
+        return result;
     }
 
     /// Sets the maximum size of the log buffer.
@@ -503,6 +1345,42 @@ impl FileStream {
         self.max_buffer_size = size.into();
     }
 
+    /// Sets the maximum size of the log buffer in bytes.
+    ///
+    /// Works alongside the maximum buffer size: the buffer is written to the
+    /// log file as soon as either limit is reached, which keeps a few huge
+    /// messages from taking up a lot of memory.
+    ///
+    /// # Examples
+    /// ```
+    /// # use prettylogger::output::FileStream;
+    /// let mut file_output = FileStream::default();
+    ///
+    /// // Flush after 128 logs or 64KiB, whichever comes first
+    /// file_output.set_max_buffer_size(Some(128));
+    /// file_output.set_max_buffer_bytes(Some(64 * 1024));
+    /// ```
+    pub fn set_max_buffer_bytes<I: Into<Option<usize>>>(&mut self, bytes: I) {
+        self.max_buffer_bytes = bytes.into();
+    }
+
+    /// Sets when written logs are synced to the disk.
+    ///
+    /// # Examples
+    /// ```
+    /// # use prettylogger::{
+    /// #     output::FileStream,
+    /// #     config::SyncPolicy,
+    /// # };
+    /// let mut file_output = FileStream::default();
+    ///
+    /// // Make sure errors survive a power loss
+    /// file_output.set_sync_policy(SyncPolicy::OnError);
+    /// ```
+    pub fn set_sync_policy<I: Into<SyncPolicy>>(&mut self, policy: I) {
+        self.sync_policy = policy.into();
+    }
+
     /// Enables the output.
     ///
     /// Returns an error if the log file is not writable.
@@ -549,6 +1427,177 @@ impl FileStream {
         self.enabled = false;
     }
 
+    /// Closes the log file and opens it again.
+    ///
+    /// The log file is kept open between flushes, and is otherwise only
+    /// reopened when the log file path changes, writing to it fails or it's
+    /// deleted.
+    /// Logs queued for the writer thread are written before the file is
+    /// closed.
+    ///
+    /// # Examples
+    /// ```
+    /// # use prettylogger::output::FileStream;
+    /// # let mut path = std::env::temp_dir();
+    /// # path.push("libprettylogger-tests/fo-reopen-doc.log");
+    /// # let path = &path.to_str().unwrap().to_string();
+    /// let mut file_output = FileStream::default();
+    /// file_output.set_log_file_path(&path)
+    ///     .expect("Failed to set the log file path!");
+    ///
+    /// file_output.reopen()
+    ///     .expect("Failed to reopen the log file!");
+    /// ```
+    pub fn reopen(&mut self) -> Result<(), Error> {
+        self.stop_writer();
+        self.log_file.0 = None;
+        self.log_file.0 = Some(LogFile::open(&self.log_file_path)?);
+        return Ok(());
+    }
+
+    /// Sets whether flushed logs are written on the logging thread or on a
+    /// dedicated writer thread.
+    ///
+    /// In `WriteMode::Asynchronous`, flushing the log buffer only moves the
+    /// logs to a bounded queue, which the writer thread writes to the log
+    /// file in the background. Dropping the stream (or the `Logger` owning
+    /// it) waits until every queued log has been written. Switching back to
+    /// `WriteMode::Synchronous` stops the thread the same way.
+    ///
+    /// # Examples
+    /// ```
+    /// # use prettylogger::{
+    /// #     output::{FileStream, Toggleable},
+    /// #     format::LogFormatter,
+    /// #     config::{LogStruct, WriteMode},
+    /// # };
+    /// # let mut path = std::env::temp_dir();
+    /// # path.push("libprettylogger-tests/fo-set_write_mode-doc.log");
+    /// # let path = &path.to_str().unwrap().to_string();
+    /// # let mut formatter = LogFormatter::default();
+    /// let mut file_output = FileStream::default();
+    /// file_output.set_log_file_path(&path)
+    ///     .expect("Failed to set the log file path!");
+    /// file_output.enable()
+    ///     .expect("Failed to enable the output!");
+    ///
+    /// // Hand every log over to the writer thread right away
+    /// file_output.set_write_mode(WriteMode::Asynchronous);
+    /// file_output.set_max_buffer_size(Some(1));
+    ///
+    /// file_output.out(&LogStruct::debug("Hello from a thread!"),
+    ///     &mut formatter).expect("Failed to queue the log!");
+    /// ```
+    pub fn set_write_mode<I: Into<WriteMode>>(&mut self, mode: I) {
+        self.write_mode = mode.into();
+        if !self.uses_writer() {
+            self.stop_writer();
+        }
+    }
+
+    /// Sets the longest time a log can wait in the buffer before being written
+    /// to the log file, or disables time-based flushing with `None`.
+    ///
+    /// The buffer is still written early when it reaches the maximum buffer
+    /// size. Since the buffer has to be flushed even when no logs are being
+    /// written, setting an interval hands the buffer over to the writer
+    /// thread, regardless of the `WriteMode`.
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::time::Duration;
+    /// # use prettylogger::output::FileStream;
+    /// let mut file_output = FileStream::default();
+    ///
+    /// // Write logs to the log file at most 500ms after they are logged
+    /// file_output.set_flush_interval(Duration::from_millis(500));
+    ///
+    /// // Only flush when the buffer is full
+    /// file_output.set_flush_interval(None);
+    /// ```
+    pub fn set_flush_interval<I: Into<Option<Duration>>>(&mut self,
+    interval: I) {
+        self.flush_interval_ms = interval.into()
+            .map(|interval| interval.as_millis() as u64);
+        if !self.uses_writer() {
+            self.stop_writer();
+        }
+    }
+
+    /// Makes errors and fatal errors flush the log buffer right away
+    /// (default).
+    pub fn enable_flush_on_error(&mut self) {
+        self.flush_on_error = true;
+    }
+
+    /// Makes errors and fatal errors wait in the log buffer like other logs.
+    pub fn disable_flush_on_error(&mut self) {
+        self.flush_on_error = false;
+    }
+
+    /// Makes the stream check whether the log file path points to a different
+    /// file before every write, and reopen it if so.
+    ///
+    /// This handles log rotation tools (e.g., `logrotate`) moving the log
+    /// file away and creating a new one in its place, which would otherwise
+    /// leave the stream writing to the moved file. If nothing was created in
+    /// its place, writing fails and the error is reported. Only supported on
+    /// Unix.
+    ///
+    /// # Examples
+    /// ```
+    /// # use prettylogger::output::FileStream;
+    /// let mut file_output = FileStream::default();
+    /// file_output.enable_rotation_detection();
+    /// ```
+    pub fn enable_rotation_detection(&mut self) {
+        self.detect_rotation = true;
+    }
+
+    /// Stops the stream from checking whether the log file was replaced
+    /// (default).
+    pub fn disable_rotation_detection(&mut self) {
+        self.detect_rotation = false;
+    }
+
+    /// Sets whether an OS-level advisory lock is held on the log file while
+    /// writing to it, so several processes can append to the same log file
+    /// without interleaving their logs.
+    ///
+    /// Unlike `lock_file`, which pauses writing until `unlock_file` is
+    /// called, the advisory lock is only held for the duration of each write.
+    ///
+    /// # Examples
+    /// ```
+    /// # use prettylogger::{
+    /// #     output::FileStream,
+    /// #     config::FileLockMode,
+    /// # };
+    /// let mut file_output = FileStream::default();
+    /// file_output.set_file_lock_mode(FileLockMode::Advisory);
+    /// ```
+    pub fn set_file_lock_mode<I: Into<FileLockMode>>(&mut self, mode: I) {
+        self.file_lock_mode = mode.into();
+    }
+
+    /// Sets what happens to flushed logs when the writer thread's queue is
+    /// full.
+    pub fn set_backpressure_policy<I: Into<BackpressurePolicy>>(&mut self,
+    policy: I) {
+        self.backpressure_policy = policy.into();
+    }
+
+    /// Sets how many logs the writer thread's queue can hold.
+    pub fn set_queue_capacity(&mut self, capacity: usize) {
+        self.queue_capacity = capacity;
+    }
+
+    /// Returns how many logs were discarded because the writer thread's
+    /// queue was full.
+    pub fn dropped_logs(&self) -> usize {
+        return self.dropped_logs;
+    }
+
     /// Sets the policy for handling the log buffer lock when the stream is
     /// dropped.
     pub fn set_on_drop_policy<I: Into<OnDropPolicy>>(&mut self, policy: I) {
@@ -573,19 +1622,489 @@ impl FileStream {
 
 impl BufferStream {
     /// Formats the given log using a formatter and stores it in a buffer.
+    ///
+    /// When the buffer is over its capacity, the oldest logs are evicted.
     pub fn out(&mut self, log: &LogStruct) {
         if self.enabled {
-            self.log_buffer.push(log.clone());
+            self.buffered_bytes += log.message.len();
+            self.log_buffer.push_back(log.clone());
+            self.evict_overflow();
         }
     }
 
-    /// Returns a reference to the internal log struct buffer.
-    pub fn get_log_buffer(&self) -> &Vec<LogStruct> {
+    /// Evicts the oldest logs until the buffer fits in its capacity. The
+    /// newest log is always kept, even if it alone exceeds the byte capacity.
+    fn evict_overflow(&mut self) {
+        let over_capacity = |buffer: &BufferStream| {
+            buffer.capacity
+                .is_some_and(|max| buffer.log_buffer.len() > max) ||
+            buffer.byte_capacity
+                .is_some_and(|max| buffer.buffered_bytes > max &&
+                    buffer.log_buffer.len() > 1)
+        };
+        while over_capacity(self) {
+            match self.log_buffer.pop_front() {
+                Some(log) => {
+                    self.buffered_bytes -= log.message.len();
+                    self.evicted_logs += 1;
+                },
+                None => break,
+            }
+        }
+    }
+
+    /// Returns a reference to the internal log struct buffer, oldest log
+    /// first.
+    pub fn get_log_buffer(&self) -> &VecDeque<LogStruct> {
         return &self.log_buffer;
     }
 
+    /// Returns a query over the log buffer, matching every log until filters
+    /// are added to it (see `LogQuery`).
+    ///
+    /// # Examples
+    /// ```
+    /// # use prettylogger::{
+    /// #     output::{BufferStream, Toggleable},
+    /// #     config::{LogStruct, LogType},
+    /// # };
+    /// let mut buffer_output = BufferStream::default();
+    /// buffer_output.enable();
+    /// buffer_output.out(&LogStruct::info("Hello!"));
+    /// buffer_output.out(&LogStruct::error("Goodbye!"));
+    ///
+    /// let errors = buffer_output.query()
+    ///     .log_type(LogType::Err)
+    ///     .fetch();
+    /// assert_eq!(errors[0].message, "Goodbye!");
+    /// ```
+    pub fn query(&self) -> LogQuery<'_> {
+        return LogQuery::new(&self.log_buffer);
+    }
+
     /// Clears the log buffer.
     pub fn clear(&mut self) {
-        self.log_buffer = Vec::new();
+        self.log_buffer = VecDeque::new();
+        self.buffered_bytes = 0;
+    }
+
+    /// Writes every log in the buffer to `writer`, oldest first, and returns
+    /// how many logs were written.
+    ///
+    /// # Examples
+    ///
+    /// Exporting the logs as JSON lines:
+    /// ```
+    /// # use prettylogger::{
+    /// #     output::{BufferStream, ExportFormat, Toggleable},
+    /// #     config::LogStruct,
+    /// # };
+    /// let mut buffer_output = BufferStream::default();
+    /// buffer_output.enable();
+    /// buffer_output.out(&LogStruct::info("Job started"));
+    ///
+    /// let mut report = Vec::new();
+    /// let logs = buffer_output.export(&mut report, ExportFormat::JsonLines)
+    ///     .expect("Failed to export the logs!");
+    /// assert_eq!(logs, 1);
+    /// ```
+    pub fn export<W: Write>(&self, writer: W, mut format: ExportFormat)
+    -> Result<usize, Error> {
+        let mut writer = BufWriter::new(writer);
+        let mut text = String::new();
+        for log in &self.log_buffer {
+            text.clear();
+            match &mut format {
+                ExportFormat::Text(formatter) => {
+                    formatter.format_log_into(log, &mut text);
+                },
+                ExportFormat::JsonLines => {
+                    match serde_json::to_string(log) {
+                        Ok(json) => text.push_str(&json),
+                        Err(e) => return Err(Error::new(&e.to_string())),
+                    }
+                    text.push('\n');
+                },
+            }
+            if let Err(e) = writer.write_all(text.as_bytes()) {
+                return Err(Error::new(&e.to_string()));
+            }
+        }
+        match writer.flush() {
+            Ok(_) => Ok(self.log_buffer.len()),
+            Err(e) => Err(Error::new(&e.to_string())),
+        }
+    }
+
+    /// Writes every log in the buffer to the file at `path`, replacing its
+    /// contents, and returns how many logs were written. The file is created
+    /// if it doesn't exist.
+    ///
+    /// # Examples
+    /// ```
+    /// # use prettylogger::{
+    /// #     output::{BufferStream, ExportFormat},
+    /// #     format::LogFormatter,
+    /// # };
+    /// # let mut path = std::env::temp_dir();
+    /// # path.push("libprettylogger-tests/bo-export_to_file-doc.log");
+    /// # let path = &path.to_str().unwrap().to_string();
+    /// # let buffer_output = BufferStream::default();
+    /// let mut formatter = LogFormatter::default();
+    /// buffer_output.export_to_file(&path, ExportFormat::Text(&mut formatter))
+    ///     .expect("Failed to export the logs!");
+    /// ```
+    pub fn export_to_file(&self, path: &str, format: ExportFormat)
+    -> Result<usize, Error> {
+        match File::create(path) {
+            Ok(file) => self.export(file, format),
+            Err(e) => Err(Error::new(&e.to_string())),
+        }
+    }
+
+    /// Writes every log in the buffer to `writer` like `export`, then clears
+    /// the buffer.
+    ///
+    /// The buffer is only cleared if every log was written, so no log is
+    /// lost when exporting fails. Since this takes the stream mutably, no
+    /// log can be added in between, and none is missed.
+    ///
+    /// # Examples
+    ///
+    /// Collecting the logs of a job:
+    /// ```
+    /// # use prettylogger::{
+    /// #     Logger,
+    /// #     output::{ExportFormat, Toggleable},
+    /// # };
+    /// let logger = Logger::default();
+    /// logger.output.buffer_output.lock().unwrap().enable();
+    ///
+    /// logger.info("Job started");
+    /// logger.info("Job finished");
+    ///
+    /// let mut report = Vec::new();
+    /// logger.output.buffer_output.lock().unwrap()
+    ///     .drain_export(&mut report, ExportFormat::JsonLines)
+    ///     .expect("Failed to export the logs!");
+    /// ```
+    pub fn drain_export<W: Write>(&mut self, writer: W, format: ExportFormat)
+    -> Result<usize, Error> {
+        let logs = self.export(writer, format)?;
+        self.clear();
+        return Ok(logs);
+    }
+
+    /// Writes every log in the buffer to the file at `path` like
+    /// `export_to_file`, then clears the buffer if every log was written.
+    pub fn drain_export_to_file(&mut self, path: &str, format: ExportFormat)
+    -> Result<usize, Error> {
+        let logs = self.export_to_file(path, format)?;
+        self.clear();
+        return Ok(logs);
+    }
+
+    /// Sets how many logs the buffer keeps. Once full, the oldest log is
+    /// evicted for every new one. `None` means no limit (default).
+    ///
+    /// # Examples
+    /// ```
+    /// # use prettylogger::output::BufferStream;
+    /// let mut buffer_output = BufferStream::default();
+    ///
+    /// // Keep the last 1000 logs
+    /// buffer_output.set_capacity(1000);
+    /// ```
+    pub fn set_capacity<I: Into<Option<usize>>>(&mut self, capacity: I) {
+        self.capacity = capacity.into();
+        self.evict_overflow();
+    }
+
+    /// Sets how many bytes of log messages the buffer keeps. Once full, the
+    /// oldest logs are evicted to make room for new ones. `None` means no
+    /// limit (default).
+    ///
+    /// # Examples
+    /// ```
+    /// # use prettylogger::output::BufferStream;
+    /// let mut buffer_output = BufferStream::default();
+    ///
+    /// // Keep the last 1 MiB of messages
+    /// buffer_output.set_byte_capacity(1024 * 1024);
+    /// ```
+    pub fn set_byte_capacity<I: Into<Option<usize>>>(&mut self, bytes: I) {
+        self.byte_capacity = bytes.into();
+        self.evict_overflow();
+    }
+
+    /// Returns how many logs were evicted to keep the buffer within its
+    /// capacity.
+    pub fn evicted_logs(&self) -> usize {
+        return self.evicted_logs;
+    }
+}
+
+/// Makes every `FileStream` reopen its log file before its next write.
+///
+/// # Examples
+///
+/// Reopening the log files after rotating them:
+/// ```
+/// # use prettylogger::output::reopen_all_file_streams;
+/// reopen_all_file_streams();
+/// ```
+pub fn reopen_all_file_streams() {
+    REOPEN_GENERATION.fetch_add(1, Ordering::AcqRel);
+}
+
+/// Starts a thread that calls `reopen_all_file_streams` whenever the process
+/// receives `SIGHUP`, the signal log rotation tools commonly send after
+/// rotating logs. Calling this more than once has no further effect.
+///
+/// # Examples
+/// ```
+/// # use prettylogger::output::install_sighup_handler;
+/// install_sighup_handler()
+///     .expect("Failed to install the SIGHUP handler!");
+/// ```
+#[cfg(unix)]
+pub fn install_sighup_handler() -> Result<(), Error> {
+    use signal_hook::{
+        consts::SIGHUP,
+        iterator::Signals,
+    };
+
+    static INSTALLED: AtomicBool = AtomicBool::new(false);
+    if INSTALLED.swap(true, Ordering::AcqRel) {
+        return Ok(());
+    }
+
+    let result = Signals::new([SIGHUP])
+        .map_err(|e| Error::new(&e.to_string()))
+        .and_then(|mut signals| {
+            std::thread::Builder::new()
+                .name(String::from("prettylogger-sighup"))
+                .spawn(move || {
+                    for _ in signals.forever() {
+                        reopen_all_file_streams();
+                    }
+                })
+                .map_err(|e| Error::new(&e.to_string()))
+        });
+    match result {
+        Ok(_) => Ok(()),
+        Err(e) => {
+            INSTALLED.store(false, Ordering::Release);
+            return Err(e);
+        },
+    }
+}
+
+impl FailoverStream {
+    /// Formats the given log and passes it to the primary `FileStream`, or to
+    /// the fallback while the primary is failing.
+    ///
+    /// A failed write to the primary is not returned as an error, since its
+    /// logs are diverted to the fallback instead.
+    pub fn out(&mut self, log: &LogStruct, formatter: &mut LogFormatter)
+        -> Result<(), Error> {
+        if !self.enabled {
+            return Err(Error::new("Output not enabled!"));
+        }
+
+        let probe = match self.retry {
+            Some(retry) => {
+                if Instant::now() < retry.retry_at {
+                    let text = formatter.format_log(log);
+                    self.divert(&text, VecDeque::from([log.clone()]), 0);
+                    return Ok(());
+                }
+                true
+            },
+            None => false,
+        };
+
+        // Only as many logs as the record keeps are needed if writing fails
+        self.evicted_pending_logs += push_bounded(&mut self.pending_logs,
+            [log.clone()], self.diverted_capacity);
+        let mut result = self.primary.out(log, formatter);
+        if probe && result.is_ok() && self.primary.buffered_logs > 0 {
+            result = self.primary.flush();
+        }
+        return self.handle_primary_result(result);
+    }
+
+    /// Flushes the primary log buffer, diverting its contents to the fallback
+    /// if writing fails.
+    pub fn flush(&mut self) -> Result<(), Error> {
+        let result = self.primary.flush();
+        return self.handle_primary_result(result);
+    }
+
+    /// Flushes the primary log buffer if it holds any logs, diverting them to
+    /// the fallback if writing fails. Does nothing when disabled.
+    pub(crate) fn flush_pending(&mut self) -> Result<(), Error> {
+        if !self.enabled {
+            return Ok(());
+        }
+        let result = self.primary.flush_pending();
+        return self.handle_primary_result(result);
+    }
+
+    /// Shuts down the primary `FileStream` (see `FileStream::shutdown`),
+    /// diverting its logs to the fallback if writing them fails, and disables
+    /// the stream.
+    ///
+    /// # Examples
+    /// ```
+    /// # use prettylogger::output::{FailoverStream, ShutdownOutcome};
+    /// let mut failover_output = FailoverStream::default();
+    ///
+    /// let outcome = failover_output.shutdown()
+    ///     .expect("Failed to shut down the output!");
+    /// assert_eq!(outcome, ShutdownOutcome::Nothing);
+    /// ```
+    pub fn shutdown(&mut self) -> Result<ShutdownOutcome, Error> {
+        let result = self.primary.shutdown();
+        let (batch, logs) = self.primary.take_failed_batch();
+        self.enabled = false;
+        self.retry = None;
+        let (pending, evicted) = self.take_pending_logs();
+        if logs == 0 {
+            return result;
+        }
+
+        self.divert(&batch, pending, evicted);
+        return match self.fallback {
+            FailoverTarget::Stderr => Ok(ShutdownOutcome::DumpedToStderr(logs)),
+            FailoverTarget::File(_) =>
+                Ok(ShutdownOutcome::WrittenToFallback(logs)),
+        };
+    }
+
+    /// Diverts the logs of a failed primary write and schedules a retry, or
+    /// marks the primary as recovered once its buffer has been written.
+    fn handle_primary_result(&mut self, result: Result<(), Error>)
+    -> Result<(), Error> {
+        let (batch, logs) = self.primary.take_failed_batch();
+        if logs > 0 {
+            let (pending, evicted) = self.take_pending_logs();
+            self.divert(&batch, pending, evicted);
+            self.schedule_retry();
+            return Ok(());
+        }
+
+        if self.primary.buffered_logs == 0 {
+            self.take_pending_logs();
+            if result.is_ok() {
+                self.retry = None;
+            }
+        }
+        return result;
+    }
+
+    /// Takes the logs waiting in the primary buffer, along with how many of
+    /// them weren't kept.
+    fn take_pending_logs(&mut self) -> (VecDeque<LogStruct>, usize) {
+        return (std::mem::take(&mut self.pending_logs),
+            std::mem::take(&mut self.evicted_pending_logs));
+    }
+
+    /// Writes formatted logs to the fallback and records them as diverted,
+    /// counting the `evicted` logs that weren't kept as evicted records.
+    ///
+    /// Falls back to `stderr` when the fallback file can't be written to
+    /// either.
+    fn divert(&mut self, text: &str, logs: VecDeque<LogStruct>,
+    evicted: usize) {
+        let result = match &self.fallback {
+            FailoverTarget::Stderr => {
+                eprint!("{}", text);
+                Ok(())
+            },
+            FailoverTarget::File(path) => create_or_append_to_file(path, text),
+        };
+        if result.is_err() {
+            eprint!("{}", text);
+        }
+        self.evicted_diverted_logs += evicted + push_bounded(
+            &mut self.diverted_logs, logs, self.diverted_capacity);
+    }
+
+    /// Doubles the retry delay (up to the maximum) and sets the next retry.
+    fn schedule_retry(&mut self) {
+        let max_delay = Duration::from_millis(self.max_retry_delay_ms);
+        let delay = match self.retry {
+            Some(retry) => (retry.delay * 2).min(max_delay),
+            None => Duration::from_millis(self.initial_retry_delay_ms)
+                .min(max_delay),
+        };
+        self.retry = Some(RetryState {
+            delay,
+            retry_at: Instant::now() + delay,
+        });
+    }
+
+    /// Sets where logs go while the primary is failing.
+    pub fn set_fallback<I: Into<FailoverTarget>>(&mut self, fallback: I) {
+        self.fallback = fallback.into();
+    }
+
+    /// Sets the delay before the first retry of a failed primary and the
+    /// maximum the delay can double up to.
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::time::Duration;
+    /// # use prettylogger::output::FailoverStream;
+    /// let mut failover_output = FailoverStream::default();
+    /// failover_output.set_retry_delay(Duration::from_millis(500),
+    ///     Duration::from_secs(30));
+    /// ```
+    pub fn set_retry_delay(&mut self, initial: Duration, max: Duration) {
+        self.initial_retry_delay_ms = initial.as_millis() as u64;
+        self.max_retry_delay_ms = max.as_millis() as u64;
+    }
+
+    /// Returns whether logs are currently being diverted to the fallback.
+    pub fn is_failing_over(&self) -> bool {
+        return self.retry.is_some();
+    }
+
+    /// Returns a reference to the record of diverted logs, oldest log first.
+    pub fn get_diverted_logs(&self) -> &VecDeque<LogStruct> {
+        return &self.diverted_logs;
+    }
+
+    /// Clears the record of diverted logs.
+    pub fn clear_diverted_logs(&mut self) {
+        self.diverted_logs = VecDeque::new();
+    }
+
+    /// Sets how many diverted logs are recorded. Once full, the oldest record
+    /// is evicted for every new one. `Some(0)` disables the record and `None`
+    /// means no limit. Defaults to 1024.
+    ///
+    /// # Examples
+    /// ```
+    /// # use prettylogger::output::FailoverStream;
+    /// let mut failover_output = FailoverStream::default();
+    ///
+    /// // Only count diverted logs
+    /// failover_output.set_diverted_capacity(0);
+    /// ```
+    pub fn set_diverted_capacity<I: Into<Option<usize>>>(&mut self,
+    capacity: I) {
+        self.diverted_capacity = capacity.into();
+        self.evicted_diverted_logs += push_bounded(&mut self.diverted_logs,
+            [], self.diverted_capacity);
+        self.evicted_pending_logs += push_bounded(&mut self.pending_logs,
+            [], self.diverted_capacity);
+    }
+
+    /// Returns how many diverted logs were evicted from (or never made it
+    /// to) the record to keep it within its capacity.
+    pub fn evicted_diverted_logs(&self) -> usize {
+        return self.evicted_diverted_logs;
     }
 }
@@ -0,0 +1,606 @@
+//! Provides `LogParser`, used to read logs formatted by `LogFormatter` back
+//! into log structs.
+
+/// Provides `LogParser`, used to read logs formatted by `LogFormatter` back
+/// into log structs.
+use std::{
+    fs::read_to_string,
+    iter::once,
+};
+
+use chrono::{
+    DateTime,
+    Datelike,
+    FixedOffset,
+    Local,
+    TimeDelta,
+    TimeZone,
+    Utc,
+    format::{
+        Fixed,
+        Item,
+        Numeric,
+        Pad,
+        Parsed,
+        StrftimeItems,
+        parse
+    },
+};
+use regex::{
+    Captures,
+    Regex,
+    escape
+};
+
+use crate::{
+    Error,
+    colors::strip_ansi_codes,
+    config::{
+        LogStruct,
+        LogType,
+        MultilineMode,
+        TimestampMode,
+        Timezone
+    },
+    format::{
+        LogFormatter,
+        indentation
+    },
+    pattern::Segment,
+};
+
+/// Log types in the order they are tried when parsing a log entry.
+const LOG_TYPES: [LogType; 5] = [
+    LogType::Debug,
+    LogType::Info,
+    LogType::Warning,
+    LogType::Err,
+    LogType::FatalError,
+];
+
+/// Matches relative timestamps, e.g. `+0.153s`.
+const RELATIVE_TIMESTAMP_REGEX: &str = r"[-+]\d+\.\d{3}s";
+
+/// Parses text produced by `LogFormatter::format_log` back into log structs,
+/// e.g. to reprocess old log files.
+///
+/// The parser is created from the `LogFormatter` that formatted the logs and
+/// uses its log formats (including per-type overrides), headers, datetime
+/// format, timezone, timestamp mode and multiline mode. ANSI escape codes are
+/// stripped before parsing, so colored output can be parsed too.
+///
+/// A line that doesn't match the beginning of any log format (everything up
+/// to the message placeholder) continues the message of the previous log.
+/// With `MultilineMode::Indent` the indentation of continuation lines is
+/// removed.
+///
+/// Logs formatted with `MultilineMode::RepeatHeader` can't be told apart from
+/// separate logs, so creating a parser from such a formatter returns an error.
+/// Switching the formatter to `MultilineMode::Verbatim` reads every line as a
+/// separate log instead.
+///
+/// Some formats can't be reversed exactly:
+/// * When several log types share the same header and log format, logs are
+///   parsed as the most verbose of them.
+/// * Relative timestamps are added to the timestamp origin of the formatter
+///   (see `set_timestamp_origin`), so they only give the original datetime
+///   when the origin is the same as when the logs were written.
+/// * Logs without a timestamp get the timestamp origin as their datetime,
+///   and datetimes missing parts of the date or time (e.g., the year) take
+///   them from it.
+/// * Local datetimes in the hour repeated when DST ends are read as the
+///   earliest one not before the previous log.
+/// * Datetimes are only as precise as the datetime format.
+///
+/// # Examples
+///
+/// Parsing logs from a string:
+/// ```
+/// # use prettylogger::{
+/// #     format::LogFormatter,
+/// #     parse::LogParser,
+/// #     config::{LogStruct, LogType, MultilineMode},
+/// # };
+/// let mut formatter = LogFormatter::default();
+/// formatter.set_multiline_mode(MultilineMode::Indent);
+///
+/// let mut text = formatter.format_log(&LogStruct::info("Starting"));
+/// text += &formatter.format_log(&LogStruct::error("Failed:\nno space"));
+///
+/// let parser = LogParser::new(&formatter)
+///     .expect("Failed to create the parser!");
+/// let logs = parser.parse_str(&text)
+///     .expect("Failed to parse the logs!");
+///
+/// assert_eq!(logs.len(), 2);
+/// assert_eq!(logs[1].log_type, LogType::Err);
+/// assert_eq!(logs[1].message, "Failed:\nno space");
+/// ```
+#[derive(Clone, Debug)]
+pub struct LogParser {
+    patterns: Vec<TypePattern>,
+    datetime_items: Vec<Item<'static>>,
+    /// Splits a datetime into the parts rendered by each datetime item.
+    datetime_parts: Regex,
+    timezone: Timezone,
+    timestamp_mode: TimestampMode,
+    multiline_mode: MultilineMode,
+    origin: DateTime<Local>,
+}
+
+/// Regular expressions matching logs of a single type.
+#[derive(Clone, Debug)]
+struct TypePattern {
+    log_type: LogType,
+    /// Matches the beginning of the first line of a log, up to the message.
+    start: Regex,
+    /// Matches a whole log, capturing its datetime and message.
+    entry: Regex,
+}
+
+/// A log matched by a `TypePattern`, before its datetime is resolved.
+struct RawLog {
+    log_type: LogType,
+    datetime: Option<String>,
+    message: String,
+}
+
+/// Translates compiled log format segments into a regular expression.
+struct RegexBuilder<'a> {
+    header: &'a str,
+    datetime: &'a str,
+    /// Whether the datetime placeholder always renders non-empty text.
+    datetime_renders: bool,
+    regex: String,
+    has_datetime: bool,
+    has_message: bool,
+}
+
+impl RegexBuilder<'_> {
+    /// Returns whether `segments` contain a placeholder that always renders
+    /// non-empty text, so a group holding them is never left out.
+    fn always_renders(&self, segments: &[Segment]) -> bool {
+        return segments.iter().any(|segment| match segment {
+            Segment::Literal(_) | Segment::Message => false,
+            Segment::Header => !self.header.is_empty(),
+            Segment::Datetime => self.datetime_renders,
+            Segment::Group(inner) => self.always_renders(inner),
+        });
+    }
+
+    /// Appends the regular expression matching `segments`. Only the first
+    /// datetime and message placeholders are captured.
+    ///
+    /// When `up_to_message` is set, stops at the first message placeholder
+    /// and returns `true`.
+    fn push(&mut self, segments: &[Segment], up_to_message: bool) -> bool {
+        for segment in segments {
+            match segment {
+                Segment::Literal(text) => self.regex.push_str(&escape(text)),
+                Segment::Header => self.regex.push_str(&escape(self.header)),
+                Segment::Datetime => {
+                    let name = if self.has_datetime { "" }
+                        else { "?P<datetime>" };
+                    self.has_datetime = true;
+                    self.regex.push_str(&format!("({name}{})",
+                        self.datetime));
+                },
+                Segment::Message => {
+                    if up_to_message {
+                        return true;
+                    }
+                    let name = if self.has_message { "" }
+                        else { "?P<message>" };
+                    self.has_message = true;
+                    self.regex.push_str(&format!("({name}.*)"));
+                },
+                Segment::Group(inner) => {
+                    // Only groups that can render empty text are optional
+                    let optional = !self.always_renders(inner);
+                    self.regex.push_str("(?:");
+                    let stopped = self.push(inner, up_to_message);
+                    self.regex.push_str(if optional { ")?" } else { ")" });
+                    if stopped {
+                        return true;
+                    }
+                },
+            }
+        }
+        return false;
+    }
+}
+
+/// Returns the number of digits a numeric datetime item is padded to, or
+/// `None` if its width varies.
+fn numeric_width(numeric: &Numeric) -> Option<usize> {
+    match numeric {
+        Numeric::Quarter | Numeric::NumDaysFromSun |
+        Numeric::WeekdayFromMon => Some(1),
+        Numeric::YearDiv100 | Numeric::YearMod100 | Numeric::IsoYearDiv100 |
+        Numeric::IsoYearMod100 | Numeric::Month | Numeric::Day |
+        Numeric::WeekFromSun | Numeric::WeekFromMon | Numeric::IsoWeek |
+        Numeric::Hour | Numeric::Hour12 | Numeric::Minute |
+        Numeric::Second => Some(2),
+        Numeric::Ordinal => Some(3),
+        Numeric::Nanosecond => Some(9),
+        _ => None,
+    }
+}
+
+/// Returns a regular expression matching a numeric datetime item.
+fn numeric_regex(numeric: &Numeric, pad: &Pad) -> String {
+    match (numeric_width(numeric), pad) {
+        (Some(width), Pad::Zero) => format!(r"\d{{{width}}}"),
+        (Some(width), Pad::Space) => {
+            format!(r" {{0,{}}}\d{{1,{width}}}", width - 1)
+        },
+        (Some(width), Pad::None) => format!(r"\d{{1,{width}}}"),
+        // Years have at least 4 digits and get a sign past 9999
+        (None, Pad::Zero) if matches!(numeric,
+            Numeric::Year | Numeric::IsoYear) => r"[-+]?\d{4,}".to_string(),
+        (None, _) => r"[-+]?\d+".to_string(),
+    }
+}
+
+/// Returns a regular expression matching datetimes rendered with `items`,
+/// capturing every item in a separate group when `capture` is set.
+///
+/// Fixed-width items get fixed-width expressions, so items rendered next to
+/// each other (e.g. `%s%3f`) are told apart. The match only has to delimit
+/// the items, `chrono` validates them later.
+fn datetime_regex(items: &[Item], capture: bool) -> String {
+    // Fractional seconds without a dot can only be compared with the items
+    // `chrono` parses them to
+    let fractions: Vec<(Option<Item>, &str)> = vec![
+        (StrftimeItems::new("%3f").next(), r"\d{3}"),
+        (StrftimeItems::new("%6f").next(), r"\d{6}"),
+        (StrftimeItems::new("%9f").next(), r"\d{9}"),
+    ];
+
+    let mut regex = String::new();
+    for item in items {
+        regex.push_str(if capture { "(" } else { "(?:" });
+        if let Some((_, fraction)) = fractions.iter()
+            .find(|(fraction, _)| fraction.as_ref() == Some(item)) {
+            regex.push_str(fraction);
+            regex.push(')');
+            continue;
+        }
+
+        match item {
+            Item::Literal(text) | Item::Space(text) => {
+                regex.push_str(&escape(text));
+            },
+            Item::OwnedLiteral(text) | Item::OwnedSpace(text) => {
+                regex.push_str(&escape(text));
+            },
+            Item::Numeric(numeric, pad) => {
+                regex.push_str(&numeric_regex(numeric, pad));
+            },
+            Item::Fixed(Fixed::ShortMonthName | Fixed::LongMonthName |
+                Fixed::ShortWeekdayName | Fixed::LongWeekdayName |
+                Fixed::LowerAmPm | Fixed::UpperAmPm) => {
+                regex.push_str("[[:alpha:]]+");
+            },
+            Item::Fixed(Fixed::Nanosecond) => regex.push_str(r"(?:\.\d+)?"),
+            Item::Fixed(Fixed::Nanosecond3) => regex.push_str(r"\.\d{3}"),
+            Item::Fixed(Fixed::Nanosecond6) => regex.push_str(r"\.\d{6}"),
+            Item::Fixed(Fixed::Nanosecond9) => regex.push_str(r"\.\d{9}"),
+            _ => regex.push_str(".*?"),
+        }
+        regex.push(')');
+    }
+    return regex;
+}
+
+/// Compiles a regular expression built by `LogParser`.
+fn compile_regex(regex: &str) -> Result<Regex, Error> {
+    return Regex::new(regex).map_err(|e| Error::new(&e.to_string()));
+}
+
+/// Parses a relative timestamp, e.g. `+0.153s`.
+fn parse_relative_timestamp(text: &str) -> Option<TimeDelta> {
+    let (seconds, millis) = text.strip_suffix('s')?.split_once('.')?;
+    let negative = seconds.starts_with('-');
+    let seconds: i64 = seconds[1..].parse().ok()?;
+    let millis: i64 = millis.parse().ok()?;
+    let delta = TimeDelta::milliseconds(seconds * 1000 + millis);
+    return Some(if negative { -delta } else { delta });
+}
+
+/// Resolves a local wall-clock datetime. When it's ambiguous, because the
+/// hour repeats when DST ends, the earliest candidate not before the previous
+/// log is picked.
+fn resolve_local(parsed: &Parsed, previous: Option<DateTime<Local>>)
+-> Option<DateTime<Local>> {
+    let naive = parsed.to_naive_datetime_with_offset(0).ok()?;
+    let candidates = Local.from_local_datetime(&naive);
+    let (mut earliest, mut latest) = (candidates.earliest()?,
+        candidates.latest()?);
+    // The candidates aren't always in order
+    if latest < earliest {
+        std::mem::swap(&mut earliest, &mut latest);
+    }
+    return match previous {
+        Some(previous) if earliest < previous => Some(latest),
+        _ => Some(earliest),
+    };
+}
+
+impl LogParser {
+    /// Creates a `LogParser` reading logs formatted by `formatter`.
+    ///
+    /// Returns an error if the formatter uses `MultilineMode::RepeatHeader`,
+    /// if a log format can start with the message (e.g., `%m (%h)`, or
+    /// `%{[%d] }%m` with an empty datetime format), since the beginning of a
+    /// log couldn't be told apart from a continuation line, or if the log
+    /// formats can't be turned into regular expressions.
+    pub fn new(formatter: &LogFormatter) -> Result<LogParser, Error> {
+        if formatter.multiline_mode == MultilineMode::RepeatHeader {
+            return Err(Error::new("Logs with repeated headers can't be told \
+                apart from separate logs, use `MultilineMode::Verbatim` to \
+                parse every line as a separate log!"));
+        }
+
+        let (datetime, datetime_renders) = match formatter.timestamp_mode {
+            TimestampMode::Absolute => {
+                let items = formatter.datetime_format.items();
+                (datetime_regex(items, false), !items.is_empty())
+            },
+            _ => (RELATIVE_TIMESTAMP_REGEX.to_string(), true),
+        };
+
+        let datetime_parts = compile_regex(&format!(r"\A{}\z",
+            datetime_regex(formatter.datetime_format.items(), true)))?;
+
+        let mut patterns = Vec::with_capacity(LOG_TYPES.len());
+        for log_type in LOG_TYPES {
+            let segments = formatter.log_pattern(log_type).segments();
+            let mut builder = RegexBuilder {
+                header: formatter.log_header(log_type),
+                datetime: &datetime,
+                datetime_renders,
+                regex: String::from(r"\A"),
+                has_datetime: false,
+                has_message: false,
+            };
+            builder.push(segments, true);
+            let start = compile_regex(&builder.regex)?;
+
+            // Any line could start a log, including continuation lines
+            if start.is_match("") {
+                return Err(Error::new(&format!("The log format of {log_type} \
+                    logs has to start with something other than the message \
+                    to tell where logs begin!")));
+            }
+
+            builder.regex = String::from(r"(?s)\A");
+            builder.has_datetime = false;
+            builder.has_message = false;
+            builder.push(segments, false);
+            builder.regex.push_str(r"\z");
+            let entry = compile_regex(&builder.regex)?;
+
+            patterns.push(TypePattern { log_type, start, entry });
+        }
+
+        return Ok(LogParser {
+            patterns,
+            datetime_items: formatter.datetime_format.items().to_vec(),
+            datetime_parts,
+            timezone: formatter.timezone,
+            timestamp_mode: formatter.timestamp_mode,
+            multiline_mode: formatter.multiline_mode,
+            origin: formatter.timestamp_origin(),
+        });
+    }
+
+    /// Sets the point in time relative timestamps are added to, which is
+    /// also the datetime of logs without a timestamp.
+    ///
+    /// Defaults to the timestamp origin of the formatter the parser was
+    /// created from.
+    pub fn set_timestamp_origin(&mut self, origin: DateTime<Local>) {
+        self.origin = origin;
+    }
+
+    /// Parses formatted logs from a string.
+    ///
+    /// Returns an error naming the first line (counted from 1) that doesn't
+    /// belong to a log, or whose log can't be parsed.
+    pub fn parse_str(&self, text: &str) -> Result<Vec<LogStruct>, Error> {
+        let text = strip_ansi_codes(text);
+        let text = text.strip_suffix('\n').unwrap_or(&text);
+        let mut logs: Vec<LogStruct> = Vec::new();
+
+        for (line_number, entry) in self.split_entries(text)? {
+            let raw = match self.match_entry(entry) {
+                Some(raw) => raw,
+                None => {
+                    return Err(Error::new(&format!(
+                        "Failed to parse the log at line {line_number}!")));
+                },
+            };
+
+            let previous = logs.last().map(|log| log.datetime);
+            let datetime = match &raw.datetime {
+                Some(text) => match self.parse_datetime(text, previous) {
+                    Some(datetime) => datetime,
+                    None => {
+                        return Err(Error::new(&format!(
+                            "Failed to parse the datetime '{text}' at line \
+                            {line_number}!")));
+                    },
+                },
+                None => self.origin,
+            };
+
+            logs.push(LogStruct {
+                datetime,
+                log_type: raw.log_type,
+                message: raw.message,
+            });
+        }
+
+        return Ok(logs);
+    }
+
+    /// Parses formatted logs from a file.
+    ///
+    /// Returns an error if the file can't be read or its contents can't be
+    /// parsed.
+    pub fn parse_file(&self, path: &str) -> Result<Vec<LogStruct>, Error> {
+        match read_to_string(path) {
+            Ok(text) => self.parse_str(&text),
+            Err(e) => Err(Error::new(&e.to_string())),
+        }
+    }
+
+    /// Splits text into log entries, each starting at a line matching the
+    /// beginning of a log format. Returns the entries along with the numbers
+    /// of their first lines.
+    fn split_entries<'a>(&self, text: &'a str)
+    -> Result<Vec<(usize, &'a str)>, Error> {
+        let mut entries: Vec<(usize, &'a str)> = Vec::new();
+        if text.is_empty() {
+            return Ok(entries);
+        }
+
+        let mut offset = 0;
+        for (i, line) in text.split('\n').enumerate() {
+            let starts_log = self.patterns.iter()
+                .any(|pattern| pattern.start.is_match(line));
+
+            if starts_log {
+                entries.push((i + 1, &text[offset..offset + line.len()]));
+            }
+            else {
+                match entries.last_mut() {
+                    Some((_, entry)) => {
+                        let start = offset - entry.len() - 1;
+                        *entry = &text[start..offset + line.len()];
+                    },
+                    None => {
+                        return Err(Error::new(&format!(
+                            "Line {} doesn't belong to any log!", i + 1)));
+                    },
+                }
+            }
+            offset += line.len() + 1;
+        }
+
+        return Ok(entries);
+    }
+
+    /// Matches a log entry against the log format of every log type.
+    fn match_entry(&self, entry: &str) -> Option<RawLog> {
+        for pattern in &self.patterns {
+            if let Some(captures) = pattern.entry.captures(entry) {
+                return Some(RawLog {
+                    log_type: pattern.log_type,
+                    datetime: captures.name("datetime")
+                        .map(|m| m.as_str().to_string()),
+                    message: self.message(entry, &captures),
+                });
+            }
+        }
+        return None;
+    }
+
+    /// Returns the captured message, removing the indentation of its
+    /// continuation lines when `MultilineMode::Indent` is set.
+    fn message(&self, entry: &str, captures: &Captures) -> String {
+        let message = match captures.name("message") {
+            Some(message) => message,
+            None => return String::new(),
+        };
+        if self.multiline_mode != MultilineMode::Indent {
+            return message.as_str().to_string();
+        }
+
+        // The indentation mirrors everything before the message on its line
+        let line_start = entry[..message.start()].rfind('\n')
+            .map_or(0, |i| i + 1);
+        let indent = indentation(&entry[line_start..message.start()]);
+
+        let mut result = String::with_capacity(message.len());
+        for (i, line) in message.as_str().split('\n').enumerate() {
+            if i > 0 {
+                result.push('\n');
+            }
+            result.push_str(line.strip_prefix(indent.as_str()).unwrap_or(line));
+        }
+        return result;
+    }
+
+    /// Parses a timestamp, adding relative timestamps to the origin or to the
+    /// datetime of the previous log.
+    fn parse_datetime(&self, text: &str, previous: Option<DateTime<Local>>)
+    -> Option<DateTime<Local>> {
+        match self.timestamp_mode {
+            TimestampMode::Absolute => (),
+            TimestampMode::SinceStart => {
+                return Some(self.origin + parse_relative_timestamp(text)?);
+            },
+            TimestampMode::SincePrevious => {
+                let reference = previous.unwrap_or(self.origin);
+                return Some(reference + parse_relative_timestamp(text)?);
+            },
+        }
+
+        // Items are parsed one by one, since `chrono` reads numbers greedily
+        // and can't tell apart items rendered next to each other
+        let parts = self.datetime_parts.captures(text)?;
+        let mut parsed = Parsed::new();
+        for (i, item) in self.datetime_items.iter().enumerate() {
+            let part = parts.get(i + 1).map_or("", |m| m.as_str());
+            parse(&mut parsed, part, once(item)).ok()?;
+        }
+
+        // Fill in whatever the datetime format doesn't contain
+        if parsed.timestamp().is_none() {
+            let origin = match self.timezone {
+                Timezone::Local => self.origin.fixed_offset(),
+                Timezone::Utc => self.origin.with_timezone(&Utc)
+                    .fixed_offset(),
+                Timezone::FixedOffset(offset) => self.origin
+                    .with_timezone(&FixedOffset::east_opt(offset)?),
+            };
+            if parsed.to_naive_date().is_err() {
+                if parsed.year().is_none() {
+                    parsed.set_year(origin.year() as i64).ok()?;
+                }
+                if parsed.month().is_none() {
+                    parsed.set_month(origin.month() as i64).ok()?;
+                }
+                if parsed.day().is_none() {
+                    parsed.set_day(origin.day() as i64).ok()?;
+                }
+            }
+            if parsed.to_naive_time().is_err() {
+                if parsed.hour_div_12().is_none() {
+                    parsed.set_hour(0).ok()?;
+                }
+                if parsed.minute().is_none() {
+                    parsed.set_minute(0).ok()?;
+                }
+            }
+        }
+
+        let datetime = match self.timezone {
+            Timezone::Local if parsed.offset().is_none() &&
+                parsed.timestamp().is_none() => {
+                resolve_local(&parsed, previous)?
+            },
+            Timezone::Local => parsed.to_datetime_with_timezone(&Local)
+                .ok()?,
+            Timezone::Utc => parsed.to_datetime_with_timezone(&Utc).ok()?
+                .with_timezone(&Local),
+            Timezone::FixedOffset(offset) => parsed
+                .to_datetime_with_timezone(&FixedOffset::east_opt(offset)?)
+                .ok()?
+                .with_timezone(&Local),
+        };
+        return Some(datetime);
+    }
+}
@@ -0,0 +1,256 @@
+use std::cmp::Ordering;
+
+use chrono::format::{
+    Item,
+    StrftimeItems
+};
+use serde::{
+    Serialize,
+    Serializer,
+    Deserialize,
+    Deserializer,
+    de::Error as DeError,
+};
+
+use crate::Error;
+
+/// A single piece of a compiled log format.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub(crate) enum Segment {
+    /// Text copied to the output as-is.
+    Literal(String),
+    /// The `%h` placeholder.
+    Header,
+    /// The `%d` placeholder.
+    Datetime,
+    /// The `%m` placeholder.
+    Message,
+    /// A conditional group (`%{...}`), omitted when all of its placeholders
+    /// render empty.
+    Group(Vec<Segment>),
+}
+
+/// A log format string compiled into a list of segments, so the format only
+/// has to be scanned once instead of on every log.
+///
+/// Serializes to (and deserializes from) the source format string, which
+/// keeps the template format unchanged.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub(crate) struct LogPattern {
+    source: String,
+    segments: Vec<Segment>,
+}
+
+impl LogPattern {
+    /// Compiles a log format string.
+    ///
+    /// Returns an error pointing at the offending character (counted from 0)
+    /// when the format contains an unknown placeholder, ends with an
+    /// unterminated `%` or leaves a conditional group open.
+    pub(crate) fn compile(format: &str) -> Result<LogPattern, Error> {
+        // Segments of the enclosing groups and the positions they start at
+        let mut groups: Vec<(Vec<Segment>, usize)> = Vec::new();
+        let mut segments = Vec::new();
+        let mut literal = String::new();
+        let mut char_iter = format.chars().enumerate();
+
+        while let Some((i, c)) = char_iter.next() {
+            if c == '}' && !groups.is_empty() {
+                flush_literal(&mut literal, &mut segments);
+                let (mut parent, _) = groups.pop()
+                    .expect("Group stack is not empty");
+                parent.push(Segment::Group(segments));
+                segments = parent;
+                continue;
+            }
+            if c != '%' {
+                literal.push(c);
+                continue;
+            }
+
+            let placeholder = match char_iter.next() {
+                Some((_, 'h')) => Segment::Header,
+                Some((_, 'd')) => Segment::Datetime,
+                Some((_, 'm')) => Segment::Message,
+                Some((_, '{')) => {
+                    flush_literal(&mut literal, &mut segments);
+                    groups.push((std::mem::take(&mut segments), i));
+                    continue;
+                },
+                Some((_, nc @ ('%' | '}'))) => {
+                    literal.push(nc);
+                    continue;
+                },
+                Some((_, 't')) => {
+                    literal.push('\t');
+                    continue;
+                },
+                Some((_, 'n')) => {
+                    literal.push('\n');
+                    continue;
+                },
+                Some((_, nc)) => {
+                    return Err(Error::new(&format!(
+                        "Unknown placeholder '%{nc}' at position {i}!")));
+                },
+                None => {
+                    return Err(Error::new(&format!(
+                        "Unterminated placeholder at position {i}!")));
+                },
+            };
+
+            flush_literal(&mut literal, &mut segments);
+            segments.push(placeholder);
+        }
+
+        if let Some((_, start)) = groups.last() {
+            return Err(Error::new(&format!(
+                "Unclosed conditional group at position {start}!")));
+        }
+
+        flush_literal(&mut literal, &mut segments);
+
+        Ok(LogPattern {
+            source: format.to_string(),
+            segments,
+        })
+    }
+
+    /// Returns the format string the pattern was compiled from.
+    pub(crate) fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    /// Returns the compiled segments.
+    pub(crate) fn segments(&self) -> &[Segment] {
+        &self.segments
+    }
+
+    /// Returns whether the pattern contains a message placeholder.
+    pub(crate) fn has_message(&self) -> bool {
+        contains_message(&self.segments)
+    }
+}
+
+/// Moves the pending literal text into `segments`.
+fn flush_literal(literal: &mut String, segments: &mut Vec<Segment>) {
+    if !literal.is_empty() {
+        segments.push(Segment::Literal(std::mem::take(literal)));
+    }
+}
+
+fn contains_message(segments: &[Segment]) -> bool {
+    segments.iter().any(|segment| match segment {
+        Segment::Message => true,
+        Segment::Group(inner) => contains_message(inner),
+        _ => false,
+    })
+}
+
+impl Serialize for LogPattern {
+    fn serialize<S: Serializer>(&self, serializer: S)
+    -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.source)
+    }
+}
+
+impl<'de> Deserialize<'de> for LogPattern {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D)
+    -> Result<Self, D::Error> {
+        let source = String::deserialize(deserializer)?;
+        LogPattern::compile(&source)
+            .map_err(|e| D::Error::custom(e.message))
+    }
+}
+
+/// A datetime format string parsed into `chrono` formatting items, so it is
+/// validated when set rather than when a log is formatted.
+///
+/// Serializes to (and deserializes from) the source format string.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub(crate) struct DatetimePattern {
+    source: String,
+    items: Vec<Item<'static>>,
+}
+
+impl DatetimePattern {
+    /// Parses a strftime-like datetime format string.
+    ///
+    /// Returns an error naming the first invalid specifier and its position
+    /// (counted in characters from 0).
+    pub(crate) fn compile(format: &str) -> Result<DatetimePattern, Error> {
+        match StrftimeItems::new(format).parse_to_owned() {
+            Ok(items) => Ok(DatetimePattern {
+                source: format.to_string(),
+                items,
+            }),
+            Err(_) => Err(invalid_specifier_error(format)),
+        }
+    }
+
+    /// Returns the parsed formatting items.
+    pub(crate) fn items(&self) -> &[Item<'static>] {
+        &self.items
+    }
+}
+
+/// Finds the first specifier `chrono` can't parse in a datetime format and
+/// returns an error describing it.
+fn invalid_specifier_error(format: &str) -> Error {
+    let mut char_iter = format.char_indices().enumerate();
+
+    while let Some((position, (start, c))) = char_iter.next() {
+        if c != '%' {
+            continue;
+        }
+
+        // A specifier is the `%`, any padding or precision modifiers and the
+        // character picking what to print
+        let mut end = format.len();
+        for (_, (i, nc)) in char_iter.by_ref() {
+            if !matches!(nc, '-' | '_' | '0'..='9' | '.' | ':' | '#') {
+                end = i + nc.len_utf8();
+                break;
+            }
+        }
+
+        let specifier = &format[start..end];
+        let is_valid = StrftimeItems::new(specifier)
+            .all(|item| item != Item::Error);
+        if !is_valid {
+            return Error::new(&format!(
+                "Invalid datetime specifier '{specifier}' at position \
+                {position}!"));
+        }
+    }
+
+    Error::new("Invalid datetime format!")
+}
+
+impl PartialOrd for DatetimePattern {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for DatetimePattern {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.source.cmp(&other.source)
+    }
+}
+
+impl Serialize for DatetimePattern {
+    fn serialize<S: Serializer>(&self, serializer: S)
+    -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.source)
+    }
+}
+
+impl<'de> Deserialize<'de> for DatetimePattern {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D)
+    -> Result<Self, D::Error> {
+        let source = String::deserialize(deserializer)?;
+        DatetimePattern::compile(&source)
+            .map_err(|e| D::Error::custom(e.message))
+    }
+}
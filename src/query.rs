@@ -0,0 +1,230 @@
+//! Provides `LogQuery`, used to search the logs stored by `BufferStream`.
+
+/// Provides `LogQuery`, used to search the logs stored by `BufferStream`.
+use std::collections::VecDeque;
+
+use chrono::{
+    DateTime,
+    Local,
+    TimeZone
+};
+use regex::Regex;
+
+use crate::{
+    Error,
+    config::{
+        LogStruct,
+        LogType
+    },
+};
+
+/// A query over the logs stored by a `BufferStream`, created with
+/// `BufferStream::query`.
+///
+/// Filters are combined, so a log has to pass all of them to match. Text
+/// filters apply to the log message, since logs don't carry any other fields.
+/// Matching logs are returned as references into the buffer, oldest first
+/// unless `newest_first` is set.
+///
+/// # Examples
+///
+/// Getting the second page of recent errors mentioning a timeout:
+/// ```
+/// # use prettylogger::{
+/// #     output::{BufferStream, Toggleable},
+/// #     config::{LogStruct, LogType},
+/// # };
+/// # use chrono::{Local, TimeDelta};
+/// let mut buffer_output = BufferStream::default();
+/// buffer_output.enable();
+/// for i in 0..30 {
+///     let error = format!("Request {i} timed out");
+///     buffer_output.out(&LogStruct::error(&error));
+///     buffer_output.out(&LogStruct::info(&format!("Request {i} done")));
+/// }
+///
+/// let page = buffer_output.query()
+///     .min_level(LogType::Err)
+///     .since(Local::now() - TimeDelta::minutes(5))
+///     .contains("timed out")
+///     .newest_first()
+///     .offset(10)
+///     .limit(10)
+///     .fetch();
+///
+/// assert_eq!(page.len(), 10);
+/// assert_eq!(page[0].message, "Request 19 timed out");
+/// ```
+#[derive(Clone, Debug)]
+pub struct LogQuery<'a> {
+    logs: &'a VecDeque<LogStruct>,
+    log_types: Vec<LogType>,
+    min_level: Option<LogType>,
+    since: Option<DateTime<Local>>,
+    until: Option<DateTime<Local>>,
+    substring: Option<String>,
+    regex: Option<Regex>,
+    newest_first: bool,
+    offset: usize,
+    limit: Option<usize>,
+}
+
+impl<'a> LogQuery<'a> {
+    /// Returns a query matching every log in `logs`.
+    pub(crate) fn new(logs: &'a VecDeque<LogStruct>) -> LogQuery<'a> {
+        LogQuery {
+            logs,
+            log_types: Vec::new(),
+            min_level: None,
+            since: None,
+            until: None,
+            substring: None,
+            regex: None,
+            newest_first: false,
+            offset: 0,
+            limit: None,
+        }
+    }
+
+    /// Only matches logs of the given type. Calling this more than once
+    /// matches logs of any of the given types.
+    pub fn log_type(mut self, log_type: LogType) -> LogQuery<'a> {
+        self.log_types.push(log_type);
+        return self;
+    }
+
+    /// Only matches logs at least as important as `log_type` (e.g.,
+    /// `LogType::Warning` matches warnings, errors and fatal errors).
+    pub fn min_level(mut self, log_type: LogType) -> LogQuery<'a> {
+        self.min_level = Some(log_type);
+        return self;
+    }
+
+    /// Only matches logs created at or after the given date and time.
+    pub fn since<Tz: TimeZone>(mut self, datetime: DateTime<Tz>)
+    -> LogQuery<'a> {
+        self.since = Some(datetime.with_timezone(&Local));
+        return self;
+    }
+
+    /// Only matches logs created before the given date and time.
+    pub fn until<Tz: TimeZone>(mut self, datetime: DateTime<Tz>)
+    -> LogQuery<'a> {
+        self.until = Some(datetime.with_timezone(&Local));
+        return self;
+    }
+
+    /// Only matches logs whose message contains `text`.
+    pub fn contains(mut self, text: &str) -> LogQuery<'a> {
+        self.substring = Some(text.to_string());
+        return self;
+    }
+
+    /// Only matches logs whose message matches the regular expression
+    /// `pattern`.
+    ///
+    /// Returns an error if the pattern is not a valid regular expression.
+    ///
+    /// # Examples
+    /// ```
+    /// # use prettylogger::{
+    /// #     output::{BufferStream, Toggleable},
+    /// #     config::LogStruct,
+    /// # };
+    /// let mut buffer_output = BufferStream::default();
+    /// buffer_output.enable();
+    /// buffer_output.out(&LogStruct::warning("Connection refused"));
+    /// buffer_output.out(&LogStruct::warning("Connection reset"));
+    ///
+    /// let logs = buffer_output.query()
+    ///     .regex(r"^Connection (refused|timed out)$")
+    ///     .expect("Invalid regular expression!")
+    ///     .fetch();
+    /// assert_eq!(logs.len(), 1);
+    ///
+    /// assert!(buffer_output.query().regex("(unclosed").is_err());
+    /// ```
+    pub fn regex(mut self, pattern: &str) -> Result<LogQuery<'a>, Error> {
+        match Regex::new(pattern) {
+            Ok(regex) => {
+                self.regex = Some(regex);
+                return Ok(self);
+            },
+            Err(e) => Err(Error::new(&e.to_string())),
+        }
+    }
+
+    /// Returns the newest matching logs first.
+    pub fn newest_first(mut self) -> LogQuery<'a> {
+        self.newest_first = true;
+        return self;
+    }
+
+    /// Skips the first `offset` matching logs.
+    pub fn offset(mut self, offset: usize) -> LogQuery<'a> {
+        self.offset = offset;
+        return self;
+    }
+
+    /// Returns at most `limit` matching logs.
+    pub fn limit(mut self, limit: usize) -> LogQuery<'a> {
+        self.limit = Some(limit);
+        return self;
+    }
+
+    /// Returns whether the log passes every filter of the query.
+    fn matches(&self, log: &LogStruct) -> bool {
+        let type_matches = self.log_types.is_empty() ||
+            self.log_types.contains(&log.log_type);
+        if !type_matches {
+            return false;
+        }
+        if self.min_level.is_some_and(|min| log.log_type < min) {
+            return false;
+        }
+        if self.since.is_some_and(|since| log.datetime < since) {
+            return false;
+        }
+        if self.until.is_some_and(|until| log.datetime >= until) {
+            return false;
+        }
+        if self.substring.as_ref()
+            .is_some_and(|text| !log.message.contains(text.as_str())) {
+            return false;
+        }
+        if self.regex.as_ref()
+            .is_some_and(|regex| !regex.is_match(&log.message)) {
+            return false;
+        }
+        return true;
+    }
+
+    /// Returns an iterator over every matching log, before the offset and
+    /// limit are applied.
+    fn all_matches(&self) -> Box<dyn Iterator<Item = &'a LogStruct> + '_> {
+        let logs = self.logs.iter();
+        if self.newest_first {
+            return Box::new(logs.rev().filter(|log| self.matches(log)));
+        }
+        return Box::new(logs.filter(|log| self.matches(log)));
+    }
+
+    /// Returns an iterator over the page of matching logs selected by the
+    /// offset and limit.
+    pub fn iter(&self) -> impl Iterator<Item = &'a LogStruct> + '_ {
+        return self.all_matches()
+            .skip(self.offset)
+            .take(self.limit.unwrap_or(usize::MAX));
+    }
+
+    /// Returns the page of matching logs selected by the offset and limit.
+    pub fn fetch(&self) -> Vec<&'a LogStruct> {
+        return self.iter().collect();
+    }
+
+    /// Returns how many logs match the query, ignoring the offset and limit.
+    /// Useful for counting pages.
+    pub fn count(&self) -> usize {
+        return self.all_matches().count();
+    }
+}
@@ -0,0 +1,70 @@
+use std::{
+    cmp::Ordering,
+    fmt::{
+        Debug,
+        Formatter
+    },
+    hash::{
+        Hash,
+        Hasher
+    },
+    ops::{
+        Deref,
+        DerefMut
+    },
+};
+
+/// Runtime state kept in a configuration struct, like an open log file or
+/// the points in time relative timestamps are measured from.
+///
+/// Runtime state is not configuration, so it never makes two structs unequal
+/// and is left out of their hashes. This lets the structs keep deriving
+/// comparison and hashing traits.
+#[derive(Clone, Default)]
+pub(crate) struct RuntimeState<T> {
+    state: T,
+}
+
+impl<T> Deref for RuntimeState<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        return &self.state;
+    }
+}
+
+impl<T> DerefMut for RuntimeState<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        return &mut self.state;
+    }
+}
+
+impl<T: Debug> Debug for RuntimeState<T> {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        return self.state.fmt(f);
+    }
+}
+
+impl<T> PartialEq for RuntimeState<T> {
+    fn eq(&self, _other: &Self) -> bool {
+        return true;
+    }
+}
+
+impl<T> Eq for RuntimeState<T> { }
+
+impl<T> PartialOrd for RuntimeState<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        return Some(self.cmp(other));
+    }
+}
+
+impl<T> Ord for RuntimeState<T> {
+    fn cmp(&self, _other: &Self) -> Ordering {
+        return Ordering::Equal;
+    }
+}
+
+impl<T> Hash for RuntimeState<T> {
+    fn hash<H: Hasher>(&self, _state: &mut H) { }
+}
@@ -1,5 +1,9 @@
 use std::{
-    sync::LazyLock,
+    time::Duration,
+    sync::{
+        Arc,
+        LazyLock
+    },
     collections::hash_map::HashMap,
     fs::{
         create_dir_all,
@@ -15,22 +19,44 @@ use rand::{
 
 use crate::{
     Logger,
+    clock::ManualClock,
     colors::{
         color_text,
         Color
     },
     config::{
+        BackpressurePolicy,
+        FailoverTarget,
+        FileLockMode,
+        LineWrap,
         LogStruct,
         LogType,
+        MultilineMode,
         OnDropPolicy,
-        Verbosity
+        OnErrorPolicy,
+        SyncPolicy,
+        TimestampMode,
+        Timezone,
+        Verbosity,
+        WriteMode
     },
     format::LogFormatter,
+    glob::{
+        self,
+        LOGGER,
+        ShutdownGuard
+    },
+    info,
     output::{
         BufferStream,
+        ExportFormat,
+        FailoverStream,
         FileStream,
+        LogOutput,
+        ShutdownOutcome,
         Toggleable
-    }
+    },
+    parse::LogParser,
 };
 
 const REPEAT_MIN: u32 = 1;
@@ -191,25 +217,14 @@ fn log_headers() {
     f.set_error_header(header);
     f.set_fatal_header(header);
 
-    if f.get_log_type_header(LogType::Debug) !=
-    f.colorify(header, f.log_header_color(LogType::Debug)) {
-        panic!("Debug headers do not match!");
-    }
-    if f.get_log_type_header(LogType::Info) !=
-    f.colorify(header, f.log_header_color(LogType::Info)) {
-        panic!("Info headers do not match!");
-    }
-    if f.get_log_type_header(LogType::Warning) !=
-    f.colorify(header, f.log_header_color(LogType::Warning)) {
-        panic!("Warning headers do not match!");
-    }
-    if f.get_log_type_header(LogType::Err) !=
-    f.colorify(header, f.log_header_color(LogType::Err)) {
-        panic!("Error headers do not match!");
-    }
-    if f.get_log_type_header(LogType::FatalError) !=
-    f.colorify(header, f.log_header_color(LogType::FatalError)) {
-        panic!("Fatal error headers do not match!");
+    f.set_log_format("%h%m").expect("Failed to set log format!");
+
+    for log_type in [LogType::Debug, LogType::Info, LogType::Warning,
+        LogType::Err, LogType::FatalError] {
+        let log = LogStruct::new("", log_type, chrono::Local::now());
+        assert_eq!(f.format_log(&log),
+            color_text(header, f.log_header_color(log_type)) + "\n",
+            "{log_type} headers do not match!");
     }
 }
 
@@ -218,7 +233,8 @@ fn log_headers() {
 fn formats() {
     let mut f = LogFormatter::default();
 
-    f.set_datetime_format("aaa");
+    f.set_datetime_format("aaa")
+        .expect("Failed to set datetime format!");
     f.set_debug_header("d");
     f.set_info_header("i");
     f.set_warning_header("W");
@@ -241,7 +257,7 @@ fn formats() {
         logstruct.log_type = *log_type;
         let comp = format!(
             "<l> <h>{}</h> <d>aaa</d> <m>aaa</m> </l>\n",
-            f.colorify(header, f.log_header_color(*log_type))
+            color_text(header, f.log_header_color(*log_type))
         );
 
         if f.format_log(&logstruct) != comp {
@@ -293,6 +309,25 @@ fn formatter_errs() {
     assert!(f.set_log_format("%m").is_ok());
     f = LogFormatter::default();
     assert!(f.set_log_format("%m %h %d").is_ok());
+
+    // Malformed formats
+    f = LogFormatter::default();
+    assert_eq!(f.set_log_format("%m %q").unwrap_err().message,
+        "Unknown placeholder '%q' at position 3!");
+    assert_eq!(f.set_log_format("ż %m %").unwrap_err().message,
+        "Unterminated placeholder at position 5!");
+    assert!(f.set_log_format("%%m").is_err());
+    assert!(serde_json::from_str::<LogFormatter>(
+        &serde_json::to_string(&LogFormatter::default()).unwrap()
+            .replace("[%h] %m", "[%h] %m %")).is_err());
+}
+
+// Test if escape sequences in log formats are expanded
+#[test]
+fn format_escapes() {
+    let mut f = LogFormatter::default();
+    f.set_log_format("%%%t%m%n%%m%%").expect("Failed to set log format!");
+    assert_eq!(f.format_log(&LogStruct::debug("aaa")), "%\taaa\n%m%\n");
 }
 
 // Test if file output is throwing errors when it should
@@ -453,3 +488,1370 @@ fn log_buffering() {
         assert!(bo_log == log);
     }
 }
+
+// Test if a buffer stream with a capacity keeps only the most recent logs
+#[test]
+fn log_buffer_capacity() {
+    let logs: Vec<LogStruct> = (0..10)
+        .map(|i| LogStruct::debug(&format!("log {i}")))
+        .collect();
+
+    // Capacity in logs
+    let mut bo = BufferStream::default();
+    bo.enable();
+    bo.set_capacity(4);
+    for log in &logs {
+        bo.out(log);
+    }
+    assert!(bo.get_log_buffer().iter().eq(logs[6..].iter()));
+    assert_eq!(bo.evicted_logs(), 6);
+
+    // Lowering the capacity evicts right away
+    bo.set_capacity(2);
+    assert!(bo.get_log_buffer().iter().eq(logs[8..].iter()));
+    assert_eq!(bo.evicted_logs(), 8);
+
+    // Capacity in bytes, each message is 5 bytes long
+    let mut bo = BufferStream::default();
+    bo.enable();
+    bo.set_byte_capacity(12);
+    for log in &logs {
+        bo.out(log);
+    }
+    assert!(bo.get_log_buffer().iter().eq(logs[8..].iter()));
+    assert_eq!(bo.evicted_logs(), 8);
+
+    // The newest log is kept even if it doesn't fit
+    bo.out(&LogStruct::debug("a message longer than the capacity"));
+    assert_eq!(bo.get_log_buffer().len(), 1);
+    assert_eq!(bo.evicted_logs(), 10);
+
+    // Clearing resets the byte count, but not the eviction counter
+    bo.clear();
+    bo.out(&logs[0]);
+    bo.out(&logs[1]);
+    assert_eq!(bo.get_log_buffer().len(), 2);
+    assert_eq!(bo.evicted_logs(), 10);
+
+    // Capacities are part of templates
+    bo.set_capacity(100);
+    let json = serde_json::to_string(&bo)
+        .expect("Failed to serialize buffer stream!");
+    let loaded: BufferStream = serde_json::from_str(&json)
+        .expect("Failed to deserialize buffer stream!");
+    assert_eq!(serde_json::to_string(&loaded)
+        .expect("Failed to serialize buffer stream!"), json);
+    assert!(json.contains("\"capacity\":100"));
+    assert!(json.contains("\"byte_capacity\":12"));
+}
+
+// Test if log queries filter and paginate the log buffer
+#[test]
+fn log_queries() {
+    use chrono::TimeZone;
+    let start = chrono::Local.with_ymd_and_hms(2025, 1, 1, 12, 0, 0).unwrap();
+    let types = [LogType::Debug, LogType::Info, LogType::Warning, LogType::Err,
+        LogType::FatalError];
+
+    let mut bo = BufferStream::default();
+    bo.enable();
+    for i in 0..20 {
+        bo.out(&LogStruct::new(&format!("log {i}"), types[i % 5],
+            start + chrono::Duration::seconds(i as i64)));
+    }
+    let messages = |logs: Vec<&LogStruct>| {
+        logs.iter().map(|log| log.message.clone()).collect::<Vec<String>>()
+    };
+
+    // No filters
+    assert_eq!(bo.query().count(), 20);
+    assert!(std::ptr::eq(bo.query().fetch()[0], &bo.get_log_buffer()[0]));
+
+    // Log types
+    assert_eq!(messages(bo.query().log_type(LogType::Err).fetch()),
+        ["log 3", "log 8", "log 13", "log 18"]);
+    assert_eq!(bo.query()
+        .log_type(LogType::Debug)
+        .log_type(LogType::Info)
+        .count(), 8);
+    assert_eq!(bo.query().min_level(LogType::Err).count(), 8);
+
+    // Time range
+    assert_eq!(messages(bo.query()
+        .since(start + chrono::Duration::seconds(5))
+        .until(start + chrono::Duration::seconds(8))
+        .fetch()), ["log 5", "log 6", "log 7"]);
+    assert_eq!(bo.query()
+        .since(start.with_timezone(&chrono::Utc))
+        .count(), 20);
+
+    // Message text
+    assert_eq!(bo.query().contains("log 1").count(), 11);
+    assert_eq!(messages(bo.query()
+        .regex(r"^log 1\d$")
+        .expect("Failed to parse the regular expression!")
+        .min_level(LogType::Warning)
+        .fetch()), ["log 12", "log 13", "log 14", "log 17", "log 18", "log 19"]);
+    assert!(bo.query().regex("log (").is_err());
+
+    // Pagination
+    let query = bo.query().min_level(LogType::Warning).offset(3).limit(4);
+    assert_eq!(messages(query.fetch()), ["log 7", "log 8", "log 9", "log 12"]);
+    assert_eq!(query.count(), 12);
+    assert_eq!(messages(bo.query()
+        .newest_first()
+        .offset(1)
+        .limit(2)
+        .fetch()), ["log 18", "log 17"]);
+    assert_eq!(bo.query().offset(30).fetch().len(), 0);
+}
+
+// Test if buffered logs are exported and drained as text and JSON lines
+#[test]
+fn log_buffer_export() {
+    create_dir_all(TMP_PATH.clone()).expect("Failed to create a directory");
+    let path = TMP_PATH.to_owned() + "/log_buffer_export.log";
+
+    let logs = [LogStruct::info("first log"), LogStruct::error("second log")];
+    let mut formatter = LogFormatter::default();
+    let formatted: String = logs.iter()
+        .map(|log| formatter.format_log(log))
+        .collect();
+
+    let mut bo = BufferStream::default();
+    bo.enable();
+    for log in &logs {
+        bo.out(log);
+    }
+
+    // Text
+    let mut text = Vec::new();
+    assert_eq!(bo.export(&mut text, ExportFormat::Text(&mut formatter))
+        .expect("Failed to export the logs!"), 2);
+    assert_eq!(String::from_utf8(text).expect("Exported invalid UTF-8!"),
+        formatted);
+    assert_eq!(bo.export_to_file(&path, ExportFormat::Text(&mut formatter))
+        .expect("Failed to export the logs!"), 2);
+    assert_eq!(read_to_string(&path).expect("Failed to read the log file!"),
+        formatted);
+
+    // JSON lines
+    let mut json = Vec::new();
+    bo.export(&mut json, ExportFormat::JsonLines)
+        .expect("Failed to export the logs!");
+    let json = String::from_utf8(json).expect("Exported invalid UTF-8!");
+    let lines: Vec<serde_json::Value> = json.lines()
+        .map(|line| serde_json::from_str(line)
+            .expect("Failed to parse a JSON line!"))
+        .collect();
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[1]["message"], "second log");
+    assert_eq!(lines[1]["log_type"], "Err");
+
+    // A failed export keeps the logs
+    let bad_path = TMP_PATH.to_owned() + "/missing-directory/export.log";
+    assert!(bo.drain_export_to_file(&bad_path, ExportFormat::JsonLines)
+        .is_err());
+    assert_eq!(bo.get_log_buffer().len(), 2);
+
+    // Draining
+    let mut drained = Vec::new();
+    assert_eq!(bo.drain_export(&mut drained, ExportFormat::JsonLines)
+        .expect("Failed to export the logs!"), 2);
+    assert_eq!(String::from_utf8(drained).expect("Exported invalid UTF-8!"),
+        json);
+    assert!(bo.get_log_buffer().is_empty());
+    assert_eq!(bo.drain_export_to_file(&path, ExportFormat::JsonLines)
+        .expect("Failed to export the logs!"), 0);
+    assert_eq!(read_to_string(&path).expect("Failed to read the log file!"),
+        "");
+}
+
+// Test if log structs round-trip through their serialized form
+#[test]
+fn log_serialization() {
+    use chrono::TimeZone;
+    let datetime = chrono::Utc.with_ymd_and_hms(2025, 1, 1, 12, 0, 0).unwrap()
+        + chrono::Duration::milliseconds(153);
+    let log = LogStruct::new("multi\nline \"message\"", LogType::FatalError,
+        datetime.with_timezone(&chrono::Local));
+
+    let json = serde_json::to_string(&log)
+        .expect("Failed to serialize the log!");
+    let value: serde_json::Value = serde_json::from_str(&json)
+        .expect("Failed to parse the serialized log!");
+    assert_eq!(value["version"], crate::config::LOG_SCHEMA_VERSION);
+    assert_eq!(value["log_type"], "FatalError");
+    assert_eq!(value["message"], log.message);
+    let serialized_datetime = chrono::DateTime::parse_from_rfc3339(
+        value["datetime"].as_str().expect("Datetime is not a string!"))
+        .expect("Failed to parse the serialized datetime!");
+    assert_eq!(serialized_datetime, datetime);
+    assert_eq!(serde_json::from_str::<LogStruct>(&json)
+        .expect("Failed to deserialize the log!"), log);
+
+    // The offset is kept as a point in time
+    let log: LogStruct = serde_json::from_str(r#"{"version":1,
+        "datetime":"2025-01-01T17:00:00.153+05:00","log_type":"Info",
+        "message":"aaa"}"#).expect("Failed to deserialize the log!");
+    assert_eq!(log.datetime, datetime);
+
+    // Unknown fields are ignored and a missing version is read as 1
+    let log: LogStruct = serde_json::from_str(r#"{"future_field":[1, 2],
+        "datetime":"2025-01-01T12:00:00.153Z","log_type":"Debug",
+        "message":"aaa"}"#).expect("Failed to deserialize the log!");
+    assert_eq!(log, LogStruct::new("aaa", LogType::Debug,
+        datetime.with_timezone(&chrono::Local)));
+
+    // Newer versions and invalid datetimes are rejected
+    assert!(serde_json::from_str::<LogStruct>(r#"{"version":2,
+        "datetime":"2025-01-01T12:00:00Z","log_type":"Debug",
+        "message":"aaa"}"#).is_err());
+    assert!(serde_json::from_str::<LogStruct>(r#"{"version":1,
+        "datetime":"yesterday","log_type":"Debug","message":"aaa"}"#)
+        .is_err());
+}
+
+// Test if formatted logs are parsed back into log structs
+#[test]
+fn log_parsing() {
+    use chrono::TimeZone;
+    let datetime = chrono::Utc.with_ymd_and_hms(2025, 1, 1, 12, 0, 0).unwrap()
+        .with_timezone(&chrono::Local);
+    let logs = vec![
+        LogStruct::new("first line", LogType::Debug, datetime),
+        LogStruct::new("multi\n\tline\n", LogType::Err,
+            datetime + chrono::Duration::seconds(1)),
+        LogStruct::new("", LogType::Warning,
+            datetime + chrono::Duration::seconds(2)),
+        LogStruct::new("[INF] looks like a log", LogType::FatalError,
+            datetime + chrono::Duration::seconds(3)),
+    ];
+    let round_trip = |f: &mut LogFormatter| {
+        let text: String = logs.iter().map(|log| f.format_log(log)).collect();
+        let parser = LogParser::new(f).expect("Failed to create the parser!");
+        return parser.parse_str(&text).expect("Failed to parse the logs!");
+    };
+
+    // Colored headers and every multiline mode
+    let mut f = LogFormatter::default();
+    f.set_log_format("%d <%h> %m |").expect("Failed to set log format!");
+    f.set_error_log_format("%{(%h) }%d: %m")
+        .expect("Failed to set log format!");
+    for mode in [MultilineMode::Verbatim, MultilineMode::Indent] {
+        f.set_multiline_mode(mode);
+        assert_eq!(round_trip(&mut f), logs);
+    }
+
+    // Repeated headers can't be told apart from separate logs
+    f.set_multiline_mode(MultilineMode::RepeatHeader);
+    assert!(LogParser::new(&f).is_err());
+    let text: String = logs.iter().map(|log| f.format_log(log)).collect();
+    f.set_multiline_mode(MultilineMode::Verbatim);
+    let parsed = LogParser::new(&f).expect("Failed to create the parser!")
+        .parse_str(&text).expect("Failed to parse the logs!");
+    assert_eq!(parsed.iter().map(|log| log.message.as_str())
+        .collect::<Vec<_>>(),
+        ["first line", "multi", "\tline", "", "", "[INF] looks like a log"]);
+
+    // Timezones and datetime formats without a date
+    f.set_timestamp_origin(datetime);
+    f.set_timezone(Timezone::FixedOffset(-5 * 3600))
+        .expect("Failed to set the timezone!");
+    f.set_datetime_format("%b %e %H:%M:%S %z")
+        .expect("Failed to set datetime format!");
+    assert_eq!(round_trip(&mut f), logs);
+    f.set_datetime_format("%H:%M:%S")
+        .expect("Failed to set datetime format!");
+    assert_eq!(round_trip(&mut f), logs);
+
+    // Every datetime format preset
+    for preset in [
+        crate::format::DATETIME_RFC3339,
+        crate::format::DATETIME_ISO8601_MILLIS,
+        crate::format::DATETIME_EPOCH_SECONDS,
+        crate::format::DATETIME_EPOCH_MILLIS,
+    ] {
+        f.set_datetime_format(preset)
+            .expect("Failed to set datetime format!");
+        assert_eq!(round_trip(&mut f), logs);
+    }
+
+    // Relative timestamps
+    for mode in [TimestampMode::SinceStart, TimestampMode::SincePrevious] {
+        f.set_timestamp_mode(mode);
+        f.set_timestamp_origin(datetime - chrono::Duration::seconds(5));
+        assert_eq!(round_trip(&mut f), logs);
+    }
+
+    // Parsing a log file
+    create_dir_all(TMP_PATH.clone()).expect("Failed to create a directory");
+    let path = TMP_PATH.to_owned() + "/log_parsing.log";
+    f.set_timestamp_origin(datetime - chrono::Duration::seconds(5));
+    let text: String = logs.iter().map(|log| f.format_log(log)).collect();
+    std::fs::write(&path, text).expect("Failed to write the log file!");
+    let mut parser = LogParser::new(&f).expect("Failed to create the parser!");
+    parser.set_timestamp_origin(datetime - chrono::Duration::seconds(5));
+    assert_eq!(parser.parse_file(&path).expect("Failed to parse the file!"),
+        logs);
+    std::fs::remove_file(&path).expect("Failed to remove the log file!");
+
+    // Text that isn't a log
+    let mut f = LogFormatter::default();
+    f.set_log_format("[%d] %m").expect("Failed to set log format!");
+    let parser = LogParser::new(&f).expect("Failed to create the parser!");
+    assert_eq!(parser.parse_str("").expect("Failed to parse the logs!"),
+        vec![]);
+    assert!(parser.parse_str("not a log\n").is_err());
+    assert!(parser.parse_str("[2025-13-01 00:00:00] x\n").is_err());
+
+    // Formats starting with the message can't tell where logs begin
+    f.set_info_log_format("%m (%h)").expect("Failed to set log format!");
+    assert!(LogParser::new(&f).is_err());
+    f.set_info_log_format("%{[%d] }%m").expect("Failed to set log format!");
+    f.set_datetime_format("").expect("Failed to set datetime format!");
+    assert!(LogParser::new(&f).is_err());
+
+    // Unless their groups always render something
+    let mut f = LogFormatter::default();
+    f.set_log_format("%{[%h] }%m").expect("Failed to set log format!");
+    let without_datetime = |logs: &[LogStruct]| logs.iter()
+        .map(|log| (log.log_type, log.message.clone())).collect::<Vec<_>>();
+    assert_eq!(without_datetime(&round_trip(&mut f)),
+        without_datetime(&logs));
+    f.set_log_format("%{%{[%d] }%h }%m").expect("Failed to set log format!");
+    assert_eq!(round_trip(&mut f), logs);
+}
+
+// Test if local datetimes in the hour repeated when DST ends are parsed
+#[test]
+fn log_parsing_dst() {
+    use chrono::TimeZone;
+
+    // `Local` follows the `TZ` variable, so the test runs again in a process
+    // pinned to a timezone with DST
+    const TZ: &str = "CET-1CEST,M3.5.0,M10.5.0/3";
+    if std::env::var("TZ").as_deref() != Ok(TZ) {
+        let output = std::process::Command::new(std::env::current_exe()
+            .expect("Failed to get the test executable!"))
+            .args(["--exact", "tests::log_parsing_dst"])
+            .env("TZ", TZ)
+            .output()
+            .expect("Failed to run the test!");
+        assert!(output.status.success(), "{}",
+            String::from_utf8_lossy(&output.stdout));
+        return;
+    }
+
+    // 02:30 CEST, 02:10 CET, 02:40 CET
+    let datetime = chrono::Utc.with_ymd_and_hms(2025, 10, 26, 0, 30, 0)
+        .unwrap().with_timezone(&chrono::Local);
+    let logs = vec![
+        LogStruct::new("aaa", LogType::Debug, datetime),
+        LogStruct::new("bbb", LogType::Debug,
+            datetime + chrono::Duration::minutes(40)),
+        LogStruct::new("ccc", LogType::Debug,
+            datetime + chrono::Duration::minutes(70)),
+    ];
+    let mut f = LogFormatter::default();
+    f.set_log_format("%d %m").expect("Failed to set log format!");
+    let text: String = logs.iter().map(|log| f.format_log(log)).collect();
+    let parser = LogParser::new(&f).expect("Failed to create the parser!");
+    assert_eq!(parser.parse_str(&text).expect("Failed to parse the logs!"),
+        logs);
+
+    // Without a previous log the earlier datetime is picked
+    assert_eq!(parser.parse_str(&f.format_log(&logs[1]))
+        .expect("Failed to parse the logs!")[0].datetime,
+        datetime - chrono::Duration::minutes(20));
+}
+
+// Test if compiled log formats survive serialization and render consistently
+#[test]
+fn compiled_formats() {
+    let mut f = LogFormatter::default();
+    f.set_datetime_format("aaa")
+        .expect("Failed to set datetime format!");
+    f.set_log_format("<%h> <%d> <%m> %%")
+        .expect("Failed to set log format!");
+
+    let json = serde_json::to_string(&f)
+        .expect("Failed to serialize formatter!");
+    assert!(json.contains("\"log_format\":\"<%h> <%d> <%m> %%\""));
+
+    let mut loaded: LogFormatter = serde_json::from_str(&json)
+        .expect("Failed to deserialize formatter!");
+    assert_eq!(f, loaded);
+
+    let log = LogStruct::info("bbb");
+    let expected = format!("<{}> <aaa> <bbb> %\n",
+        color_text("INF", f.log_header_color(LogType::Info)));
+    assert_eq!(f.format_log(&log), expected);
+    assert_eq!(loaded.format_log(&log), expected);
+
+    // Formatting into a buffer appends to it
+    let mut buf = String::new();
+    loaded.format_log_into(&log, &mut buf);
+    loaded.format_log_into(&log, &mut buf);
+    assert_eq!(buf, expected.repeat(2));
+}
+
+// Test if conditional groups are omitted when their placeholders are empty
+#[test]
+fn conditional_groups() {
+    let mut f = LogFormatter::default();
+    f.disable_log_header_color();
+    f.set_log_format("%{[%d] }%{<%h%{ %d}>}%{ no placeholders} %m%{ %}}")
+        .expect("Failed to set log format!");
+
+    f.set_datetime_format("aaa")
+        .expect("Failed to set datetime format!");
+    assert_eq!(f.format_log(&LogStruct::debug("bbb")),
+        "[aaa] <DBG aaa> bbb\n");
+
+    f.set_datetime_format("")
+        .expect("Failed to set datetime format!");
+    assert_eq!(f.format_log(&LogStruct::debug("bbb")), "<DBG> bbb\n");
+
+    f.set_debug_header("");
+    assert_eq!(f.format_log(&LogStruct::debug("bbb")), " bbb\n");
+
+    // The message placeholder may be inside a group
+    assert!(f.set_log_format("%{%m}").is_ok());
+    assert_eq!(f.set_log_format("%m %{%h %{%d}").unwrap_err().message,
+        "Unclosed conditional group at position 3!");
+}
+
+// Test if per-level log formats override the global log format
+#[test]
+fn log_format_overrides() {
+    let mut f = LogFormatter::default();
+    f.disable_log_header_color();
+    f.set_datetime_format("aaa")
+        .expect("Failed to set datetime format!");
+    f.set_log_format("%m").expect("Failed to set log format!");
+    f.set_error_log_format("[%h] %d %m")
+        .expect("Failed to set error log format!");
+
+    assert_eq!(f.format_log(&LogStruct::info("bbb")), "bbb\n");
+    assert_eq!(f.format_log(&LogStruct::error("bbb")), "[ERR] aaa bbb\n");
+
+    // Overrides are validated like the global log format
+    assert!(f.set_fatal_log_format("%h %d").is_err());
+    assert!(f.set_fatal_log_format("%m %").is_err());
+
+    // Overrides are persisted in templates
+    let json = serde_json::to_string(&f)
+        .expect("Failed to serialize formatter!");
+    let mut loaded: LogFormatter = serde_json::from_str(&json)
+        .expect("Failed to deserialize formatter!");
+    assert_eq!(loaded.format_log(&LogStruct::error("bbb")),
+        "[ERR] aaa bbb\n");
+
+    // Templates with formats missing the message placeholder are rejected
+    let without_message = json.replace("[%h] %d %m", "%h only");
+    assert!(serde_json::from_str::<LogFormatter>(&without_message).is_err());
+    let without_message = json.replace("\"log_format\":\"%m\"",
+        "\"log_format\":\"no message\"");
+    assert_ne!(without_message, json);
+    assert!(serde_json::from_str::<LogFormatter>(&without_message).is_err());
+
+    f.set_error_log_format(None)
+        .expect("Failed to remove error log format!");
+    assert_eq!(f.format_log(&LogStruct::error("bbb")), "bbb\n");
+}
+
+// Test if multi-line messages are laid out according to the multiline mode
+#[test]
+fn multiline_messages() {
+    let log = LogStruct::warning("aaa\nbbb\nccc");
+    let mut f = LogFormatter::default();
+    f.set_log_format("[%h]%t%{<%d>} %m").expect("Failed to set log format!");
+    f.set_datetime_format("")
+        .expect("Failed to set datetime format!");
+
+    assert_eq!(f.format_log(&log), format!("[{}]\t aaa\nbbb\nccc\n",
+        color_text("WAR", f.log_header_color(LogType::Warning))));
+
+    // Colors are not counted towards the indent, tabs are kept
+    f.set_multiline_mode(MultilineMode::Indent);
+    assert_eq!(f.format_log(&log), format!(
+        "[{}]\t aaa\n     \t bbb\n     \t ccc\n",
+        color_text("WAR", f.log_header_color(LogType::Warning))));
+
+    f.disable_log_header_color();
+    f.set_multiline_mode(MultilineMode::RepeatHeader);
+    assert_eq!(f.format_log(&log),
+        "[WAR]\t aaa\n[WAR]\t bbb\n[WAR]\t ccc\n");
+}
+
+// Test if messages printed to a terminal are wrapped at word boundaries
+#[test]
+fn line_wrapping() {
+    let log = LogStruct::info("aaa bbb ccc ddd\neee fff");
+    let mut f = LogFormatter::default();
+    f.set_log_format("[%h] %m").expect("Failed to set log format!");
+    f.set_line_wrap(LineWrap::Fixed(14));
+
+    // Colors don't count towards the width
+    assert_eq!(f.format_log_for_terminal(&log), format!(
+        "[{}] aaa bbb\n      ccc ddd\neee fff\n",
+        color_text("INF", f.log_header_color(LogType::Info))));
+
+    f.disable_log_header_color();
+    f.set_multiline_mode(MultilineMode::Indent);
+    assert_eq!(f.format_log_for_terminal(&log),
+        "[INF] aaa bbb\n      ccc ddd\n      eee fff\n");
+
+    // Words longer than the line are left intact
+    assert_eq!(f.format_log_for_terminal(&LogStruct::info("aaaaaaaaaa b")),
+        "[INF] aaaaaaaaaa\n      b\n");
+
+    // Wide characters take up two columns
+    f.set_log_format("[%h] 日本 %m").expect("Failed to set log format!");
+    f.set_line_wrap(LineWrap::Fixed(22));
+    assert_eq!(f.format_log_for_terminal(&LogStruct::info("語語 aa 😀😀")),
+        "[INF] 日本 語語 aa\n           😀😀\n");
+    f.set_log_format("[%h] %m").expect("Failed to set log format!");
+    f.set_line_wrap(LineWrap::Fixed(14));
+
+    // Other outputs are never wrapped
+    assert_eq!(f.format_log(&log), "[INF] aaa bbb ccc ddd\n      eee fff\n");
+
+    let json = serde_json::to_string(&f)
+        .expect("Failed to serialize formatter!");
+    let loaded: LogFormatter = serde_json::from_str(&json)
+        .expect("Failed to deserialize formatter!");
+    assert_eq!(loaded.line_wrap, LineWrap::Fixed(14));
+}
+
+// Test if timestamps are rendered in the configured timezone
+#[test]
+fn timezones() {
+    let log = LogStruct::info("aaa");
+    let mut f = LogFormatter::default();
+    f.set_log_format("%d %m").expect("Failed to set log format!");
+    f.set_datetime_format("%Y-%m-%d %H:%M:%S %:z")
+        .expect("Failed to set datetime format!");
+
+    assert_eq!(f.format_log(&log), format!("{} aaa\n",
+        log.datetime.format("%Y-%m-%d %H:%M:%S %:z")));
+
+    f.set_timezone(Timezone::Utc).expect("Failed to set timezone!");
+    assert_eq!(f.format_log(&log), format!("{} aaa\n",
+        log.datetime.naive_utc().format("%Y-%m-%d %H:%M:%S +00:00")));
+
+    f.set_timezone(Timezone::FixedOffset(-5400))
+        .expect("Failed to set timezone!");
+    let shifted = log.datetime.naive_utc() - chrono::Duration::seconds(5400);
+    assert_eq!(f.format_log(&log), format!("{} aaa\n",
+        shifted.format("%Y-%m-%d %H:%M:%S -01:30")));
+
+    assert!(f.set_timezone(Timezone::FixedOffset(86400)).is_err());
+
+    let json = serde_json::to_string(&f)
+        .expect("Failed to serialize formatter!");
+    let loaded: LogFormatter = serde_json::from_str(&json)
+        .expect("Failed to deserialize formatter!");
+    assert_eq!(loaded.timezone, Timezone::FixedOffset(-5400));
+
+    // Invalid offsets are rejected when loading templates as well
+    let json = json.replace("-5400", "999999");
+    assert!(serde_json::from_str::<LogFormatter>(&json).is_err());
+}
+
+// Test if relative timestamps are measured from the right point in time
+#[test]
+fn relative_timestamps() {
+    let origin = chrono::Local::now();
+    let mut first = LogStruct::info("aaa");
+    first.datetime = origin + chrono::Duration::milliseconds(153);
+    let mut second = LogStruct::info("bbb");
+    second.datetime = origin + chrono::Duration::milliseconds(2500);
+
+    let mut f = LogFormatter::default();
+    f.set_log_format("%d %m").expect("Failed to set log format!");
+    f.set_timestamp_origin(origin);
+
+    f.set_timestamp_mode(TimestampMode::SinceStart);
+    assert_eq!(f.format_log(&first), "+0.153s aaa\n");
+    assert_eq!(f.format_log(&second), "+2.500s bbb\n");
+
+    f.set_timestamp_mode(TimestampMode::SincePrevious);
+    assert_eq!(f.format_log(&first), "+0.153s aaa\n");
+    assert_eq!(f.format_log(&second), "+2.347s bbb\n");
+    assert_eq!(f.format_log(&first), "-2.347s aaa\n");
+
+    // Different logs created at the same instant
+    let mut third = LogStruct::info("ccc");
+    third.datetime = first.datetime;
+    assert_eq!(f.format_log(&third), "+0.000s ccc\n");
+
+    // Relative timestamps don't make formatters unequal
+    f.set_timestamp_mode(TimestampMode::Absolute);
+    assert_eq!(f, LogFormatter {
+        log_format: f.log_format.clone(),
+        ..Default::default()
+    });
+}
+
+// Test if `Logger` timestamps logs using its clock
+#[test]
+fn manual_clock() {
+    let start = chrono::Local::now();
+    let clock = Arc::new(ManualClock::new(start));
+
+    let mut l = Logger::default();
+    l.set_clock(clock.clone());
+    l.output.stderr_output.disable();
+    l.output.buffer_output.lock().unwrap().enable();
+    l.formatter.lock().unwrap().set_timestamp_mode(TimestampMode::SinceStart);
+
+    l.info("aaa");
+    clock.advance(chrono::Duration::milliseconds(1500));
+    l.error("bbb");
+
+    let buffer_output = l.output.buffer_output.lock().unwrap();
+    let logs = buffer_output.get_log_buffer();
+    assert_eq!(logs[0], LogStruct::new("aaa", LogType::Info, start));
+    assert_eq!(logs[1].datetime,
+        start + chrono::Duration::milliseconds(1500));
+
+    let mut f = l.formatter.lock().unwrap();
+    f.set_log_format("%d %m").expect("Failed to set log format!");
+    assert_eq!(f.format_log(&logs[1]), "+1.500s bbb\n");
+}
+
+// Test if every output renders the same relative timestamp for a log
+#[test]
+fn relative_timestamps_across_outputs() {
+    create_dir_all(TMP_PATH.clone()).expect("Failed to create a directory");
+    let path = TMP_PATH.to_owned() + "/relative_timestamps.log";
+    let failover_path = TMP_PATH.to_owned() +
+        "/relative_timestamps-failover.log";
+
+    let start = chrono::Local::now();
+    let clock = Arc::new(ManualClock::new(start));
+    let mut l = Logger::default();
+    l.set_clock(clock.clone());
+    l.output.stderr_output.disable();
+    for (fo, path) in [
+        (&mut *l.output.file_output.lock().unwrap(), &path),
+        (&mut l.output.failover_output.lock().unwrap().primary,
+            &failover_path),
+    ] {
+        fo.set_log_file_path(path).expect("Failed to set the log file path!");
+        fo.enable().expect("Failed to enable file output!");
+    }
+    l.output.failover_output.lock().unwrap().enable();
+    {
+        let mut f = l.formatter.lock().unwrap();
+        f.set_log_format("%d %m").expect("Failed to set log format!");
+        f.set_timestamp_mode(TimestampMode::SincePrevious);
+        f.set_timestamp_origin(start);
+    }
+
+    // Two different logs at the same instant
+    clock.advance(chrono::Duration::seconds(5));
+    l.info("aaa");
+    l.info("bbb");
+    clock.advance(chrono::Duration::milliseconds(1500));
+    l.info("ccc");
+    l.flush().expect("Failed to flush the logger!");
+
+    for path in [&path, &failover_path] {
+        assert_eq!(read_to_string(path).expect("Failed to read the log file!"),
+            "+5.000s aaa\n+0.000s bbb\n+1.500s ccc\n");
+        std::fs::remove_file(path).expect("Failed to remove the log file!");
+    }
+}
+
+// Test if datetime formats are validated and presets render as expected
+#[test]
+fn datetime_formats() {
+    use chrono::TimeZone;
+    use crate::format::{
+        DATETIME_RFC3339,
+        DATETIME_ISO8601_MILLIS,
+        DATETIME_EPOCH_SECONDS,
+        DATETIME_EPOCH_MILLIS,
+    };
+
+    let mut f = LogFormatter::default();
+    assert_eq!(f.set_datetime_format("%Y-%m-%d %Q").unwrap_err().message,
+        "Invalid datetime specifier '%Q' at position 9!");
+    assert_eq!(f.set_datetime_format("ż %-Q").unwrap_err().message,
+        "Invalid datetime specifier '%-Q' at position 2!");
+    assert!(f.set_datetime_format("%H:%M %").is_err());
+    assert!(f.set_datetime_format("%H:%M:%S%.3f %% %-d %:z").is_ok());
+
+    // Invalid formats are rejected when loading templates as well
+    let json = serde_json::to_string(&LogFormatter::default())
+        .expect("Failed to serialize formatter!")
+        .replace("%H:%M:%S", "%H:%M:%Q");
+    assert!(serde_json::from_str::<LogFormatter>(&json).is_err());
+
+    let datetime = chrono::Utc.with_ymd_and_hms(2025, 1, 1, 12, 0, 0).unwrap()
+        + chrono::Duration::milliseconds(153);
+    let log = LogStruct::new("aaa", LogType::Info,
+        datetime.with_timezone(&chrono::Local));
+    f.set_log_format("%d %m").expect("Failed to set log format!");
+    f.set_timezone(Timezone::Utc).expect("Failed to set timezone!");
+
+    let presets = [
+        (DATETIME_RFC3339, "2025-01-01T12:00:00+00:00 aaa\n"),
+        (DATETIME_ISO8601_MILLIS, "2025-01-01T12:00:00.153+00:00 aaa\n"),
+        (DATETIME_EPOCH_SECONDS, "1735732800 aaa\n"),
+        (DATETIME_EPOCH_MILLIS, "1735732800153 aaa\n"),
+    ];
+    for (format, expected) in presets {
+        f.set_datetime_format(format)
+            .expect("Failed to set datetime format!");
+        assert_eq!(f.format_log(&log), expected);
+    }
+}
+
+// Test if failed log file writes are counted and handled by the error policy
+#[test]
+fn output_errors() {
+    create_dir_all(TMP_PATH.clone()).expect("Failed to create a directory");
+    let path = TMP_PATH.to_owned() + "/output_errors.log";
+
+    let log = LogStruct::warning("example warning");
+    let mut formatter = LogFormatter::default();
+
+    let mut output = LogOutput::default();
+    output.stderr_output.disable();
+    {
+        let mut fo = output.file_output.lock().unwrap();
+        fo.set_log_file_path(&path)
+            .expect("Failed to set the log file path!");
+        fo.enable().expect("Failed to enable file output!");
+        fo.set_max_buffer_size(Some(2));
+    }
+    std::fs::remove_file(&path).expect("Failed to remove the log file!");
+
+    // Every second log triggers a failing flush
+    output.set_on_error_policy(OnErrorPolicy::Ignore);
+    for _ in 0..4 {
+        output.out(&log, &mut formatter);
+    }
+    assert_eq!(output.failed_writes(), 2);
+    assert_eq!(output.failed_logs(), 4);
+
+    // The callback receives the logs that were not written
+    let lost = Arc::new(std::sync::Mutex::new(String::new()));
+    let lost_clone = lost.clone();
+    output.set_error_callback(move |_, logs| {
+        lost_clone.lock().unwrap().push_str(logs);
+    });
+    output.reset_failed_writes();
+    output.out(&log, &mut formatter);
+    output.out(&log, &mut formatter);
+    assert_eq!(output.failed_writes(), 1);
+    assert_eq!(output.failed_logs(), 2);
+    assert_eq!(*lost.lock().unwrap(), formatter.format_log(&log).repeat(2));
+
+    // The error policy is part of templates
+    output.set_on_error_policy(OnErrorPolicy::FailoverToStderr);
+    let json = serde_json::to_string(&output)
+        .expect("Failed to serialize log output!");
+    let loaded: LogOutput = serde_json::from_str(&json)
+        .expect("Failed to deserialize log output!");
+    assert!(loaded == output);
+    assert_eq!(loaded.failed_writes(), 0);
+}
+
+// Test if failover stream diverts logs and recovers when the primary does
+#[test]
+fn failover_output() {
+    create_dir_all(TMP_PATH.clone()).expect("Failed to create a directory");
+    let path = TMP_PATH.to_owned() + "/failover_output.log";
+    let fallback_path = TMP_PATH.to_owned() + "/failover_output_fb.log";
+    let _ = std::fs::remove_file(&fallback_path);
+
+    let log = LogStruct::warning("example warning");
+    let mut formatter = LogFormatter::default();
+    let formatted = formatter.format_log(&log);
+
+    let mut fo = FailoverStream::default();
+    assert!(fo.out(&log, &mut formatter).is_err());
+
+    fo.primary.set_log_file_path(&path)
+        .expect("Failed to set the log file path!");
+    fo.primary.enable().expect("Failed to enable file output!");
+    fo.primary.set_max_buffer_size(Some(2));
+    fo.set_fallback(FailoverTarget::File(fallback_path.clone()));
+    fo.set_retry_delay(Duration::ZERO, Duration::ZERO);
+    fo.enable();
+
+    // Buffered logs are diverted once flushing the primary fails
+    std::fs::remove_file(&path).expect("Failed to remove the log file!");
+    fo.out(&log, &mut formatter).expect("Failed to out to failover output!");
+    assert!(!fo.is_failing_over());
+    fo.out(&log, &mut formatter).expect("Failed to out to failover output!");
+    assert!(fo.is_failing_over());
+    assert_eq!(fo.get_diverted_logs().len(), 2);
+
+    // Each retry is flushed right away
+    fo.out(&log, &mut formatter).expect("Failed to out to failover output!");
+    assert_eq!(fo.get_diverted_logs().len(), 3);
+    assert_eq!(read_to_string(&fallback_path)
+        .expect("Failed to read the fallback file!"), formatted.repeat(3));
+
+    // The primary recovers
+    std::fs::File::create(&path).expect("Failed to create the log file!");
+    fo.out(&log, &mut formatter).expect("Failed to out to failover output!");
+    assert!(!fo.is_failing_over());
+    assert_eq!(read_to_string(&path)
+        .expect("Failed to read the log file!"), formatted);
+
+    // Logs go straight to the fallback until the retry delay passes
+    fo.clear_diverted_logs();
+    fo.set_retry_delay(Duration::from_secs(3600), Duration::from_secs(3600));
+    std::fs::remove_file(&path).expect("Failed to remove the log file!");
+    assert!(fo.primary.reopen().is_err());
+    fo.out(&log, &mut formatter).expect("Failed to out to failover output!");
+    fo.out(&log, &mut formatter).expect("Failed to out to failover output!");
+    std::fs::File::create(&path).expect("Failed to create the log file!");
+    fo.out(&log, &mut formatter).expect("Failed to out to failover output!");
+    assert!(fo.is_failing_over());
+    assert_eq!(fo.get_diverted_logs().len(), 3);
+    assert_eq!(read_to_string(&path)
+        .expect("Failed to read the log file!"), "");
+
+    // The record of diverted logs is bounded
+    fo.set_diverted_capacity(2);
+    assert_eq!(fo.get_diverted_logs().len(), 2);
+    assert_eq!(fo.evicted_diverted_logs(), 1);
+    fo.out(&log, &mut formatter).expect("Failed to out to failover output!");
+    assert_eq!(fo.get_diverted_logs().len(), 2);
+    assert_eq!(fo.evicted_diverted_logs(), 2);
+
+    // Logs waiting in the primary buffer are only counted when the record
+    // is disabled
+    let mut fo = FailoverStream::default();
+    fo.primary.set_log_file_path(&path)
+        .expect("Failed to set the log file path!");
+    fo.primary.enable().expect("Failed to enable file output!");
+    fo.primary.set_max_buffer_size(Some(3));
+    fo.set_fallback(FailoverTarget::File(fallback_path.clone()));
+    fo.set_diverted_capacity(0);
+    fo.enable();
+    fo.out(&log, &mut formatter).expect("Failed to out to failover output!");
+    fo.out(&log, &mut formatter).expect("Failed to out to failover output!");
+    std::fs::remove_file(&path).expect("Failed to remove the log file!");
+    assert!(fo.primary.reopen().is_err());
+    fo.out(&log, &mut formatter).expect("Failed to out to failover output!");
+    assert!(fo.is_failing_over());
+    assert!(fo.get_diverted_logs().is_empty());
+    assert_eq!(fo.evicted_diverted_logs(), 3);
+}
+
+// Test if asynchronous file output writes every log it doesn't drop
+#[test]
+fn async_file_output() {
+    create_dir_all(TMP_PATH.clone()).expect("Failed to create a directory");
+
+    let log = LogStruct::info("example info message");
+    let mut formatter = LogFormatter::default();
+    let formatted = formatter.format_log(&log);
+    let n = 256;
+
+    let policies = [
+        BackpressurePolicy::Block,
+        BackpressurePolicy::DropNewest,
+        BackpressurePolicy::DropOldest,
+    ];
+    for policy in policies {
+        let path = format!("{}/async_file_output_{}.log", *TMP_PATH, policy);
+
+        let mut fo = FileStream::default();
+        fo.set_log_file_path(&path)
+            .expect("Failed to set the log file path!");
+        fo.enable().expect("Failed to enable file output!");
+        fo.set_write_mode(WriteMode::Asynchronous);
+        fo.set_max_buffer_size(Some(1));
+        fo.set_queue_capacity(4);
+        fo.set_backpressure_policy(policy);
+
+        for _ in 0..n {
+            fo.out(&log, &mut formatter)
+                .expect("Failed to out to a file output!");
+        }
+        assert!(fo.flush().is_ok());
+
+        let contents = read_to_string(&path)
+            .expect("Failed to read the log file!");
+        let written = contents.matches(&formatted).count();
+        assert_eq!(written + fo.dropped_logs(), n);
+        if policy == BackpressurePolicy::Block {
+            assert_eq!(fo.dropped_logs(), 0);
+        }
+    }
+
+    // Queued logs are written before the stream is dropped
+    let path = TMP_PATH.to_owned() + "/async_file_output_drop.log";
+    let mut fo = FileStream::default();
+    fo.set_log_file_path(&path).expect("Failed to set the log file path!");
+    fo.enable().expect("Failed to enable file output!");
+    fo.set_write_mode(WriteMode::Asynchronous);
+    fo.set_max_buffer_size(Some(8));
+    for _ in 0..n {
+        fo.out(&log, &mut formatter).expect("Failed to out to a file output!");
+    }
+    drop(fo);
+    assert_eq!(read_to_string(&path).expect("Failed to read the log file!"),
+        formatted.repeat(n));
+
+    // Failed background writes are reported on flush
+    let path = TMP_PATH.to_owned() + "/async_file_output_err.log";
+    let mut fo = FileStream::default();
+    fo.set_log_file_path(&path).expect("Failed to set the log file path!");
+    fo.enable().expect("Failed to enable file output!");
+    fo.set_write_mode(WriteMode::Asynchronous);
+    std::fs::remove_file(&path).expect("Failed to remove the log file!");
+    fo.out(&log, &mut formatter).expect("Failed to out to a file output!");
+    assert!(fo.flush().is_err());
+    assert_eq!(fo.take_failed_batch(), (formatted.clone(), 1));
+}
+
+// Test if the log buffer is flushed on errors and after the flush interval
+#[test]
+fn flush_interval() {
+    create_dir_all(TMP_PATH.clone()).expect("Failed to create a directory");
+    let path = TMP_PATH.to_owned() + "/flush_interval.log";
+
+    let debug_log = LogStruct::debug("example debug message");
+    let error_log = LogStruct::error("example error message");
+    let mut formatter = LogFormatter::default();
+    let both = formatter.format_log(&debug_log) +
+        &formatter.format_log(&error_log);
+
+    let read_log_file = || read_to_string(&path)
+        .expect("Failed to read the log file!");
+    let wait_for_log_file = |expected: &str| {
+        for _ in 0..500 {
+            if read_log_file() == expected {
+                return;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(read_log_file(), expected);
+    };
+
+    // Errors are flushed right away
+    let mut fo = FileStream::default();
+    fo.set_log_file_path(&path).expect("Failed to set the log file path!");
+    fo.enable().expect("Failed to enable file output!");
+    fo.out(&debug_log, &mut formatter).expect("Failed to out to file output!");
+    assert_eq!(read_log_file(), "");
+    fo.out(&error_log, &mut formatter).expect("Failed to out to file output!");
+    assert_eq!(read_log_file(), both);
+
+    // Unless disabled
+    fo.set_log_file_path(&path).expect("Failed to set the log file path!");
+    fo.disable_flush_on_error();
+    fo.out(&error_log, &mut formatter).expect("Failed to out to file output!");
+    assert_eq!(read_log_file(), "");
+
+    // Errors skip the flush interval
+    let mut fo = FileStream::default();
+    fo.set_log_file_path(&path).expect("Failed to set the log file path!");
+    fo.enable().expect("Failed to enable file output!");
+    fo.set_flush_interval(Duration::from_secs(3600));
+    fo.out(&debug_log, &mut formatter).expect("Failed to out to file output!");
+    fo.out(&error_log, &mut formatter).expect("Failed to out to file output!");
+    wait_for_log_file(&both);
+    assert!(fo.flush().is_ok());
+
+    // Other logs are written once the interval passes
+    fo.set_log_file_path(&path).expect("Failed to set the log file path!");
+    fo.set_flush_interval(Duration::from_millis(50));
+    fo.out(&debug_log, &mut formatter).expect("Failed to out to file output!");
+    fo.out(&debug_log, &mut formatter).expect("Failed to out to file output!");
+    wait_for_log_file(&formatter.format_log(&debug_log).repeat(2));
+
+    // The settings are part of templates
+    let json = serde_json::to_string(&fo)
+        .expect("Failed to serialize file output!");
+    let loaded: FileStream = serde_json::from_str(&json)
+        .expect("Failed to deserialize file output!");
+    assert!(json.contains("\"flush_interval_ms\":50"));
+    assert!(json.contains("\"flush_on_error\":true"));
+    assert_eq!(serde_json::to_string(&loaded)
+        .expect("Failed to serialize file output!"), json);
+}
+
+// Test if the byte limit flushes the log buffer and syncing keeps logs intact
+#[test]
+fn buffer_byte_limit() {
+    create_dir_all(TMP_PATH.clone()).expect("Failed to create a directory");
+    let path = TMP_PATH.to_owned() + "/buffer_byte_limit.log";
+
+    let log = LogStruct::debug(&"a".repeat(1024));
+    let mut formatter = LogFormatter::default();
+    let formatted = formatter.format_log(&log);
+
+    let mut fo = FileStream::default();
+    fo.set_log_file_path(&path).expect("Failed to set the log file path!");
+    fo.enable().expect("Failed to enable file output!");
+    fo.set_max_buffer_size(None);
+    fo.set_max_buffer_bytes(Some(formatted.len() * 2 + 1));
+
+    for _ in 0..2 {
+        fo.out(&log, &mut formatter).expect("Failed to out to file output!");
+    }
+    assert_eq!(read_to_string(&path).expect("Failed to read the log file!"),
+        "");
+    fo.out(&log, &mut formatter).expect("Failed to out to file output!");
+    assert_eq!(read_to_string(&path).expect("Failed to read the log file!"),
+        formatted.repeat(3));
+
+    let error_log = LogStruct::error("example error message");
+    let policies = [
+        SyncPolicy::Never,
+        SyncPolicy::EveryFlush,
+        SyncPolicy::OnError,
+    ];
+    for policy in policies {
+        for mode in [WriteMode::Synchronous, WriteMode::Asynchronous] {
+            fo.set_log_file_path(&path)
+                .expect("Failed to set the log file path!");
+            fo.set_sync_policy(policy);
+            fo.set_write_mode(mode);
+            fo.out(&log, &mut formatter)
+                .expect("Failed to out to file output!");
+            fo.out(&error_log, &mut formatter)
+                .expect("Failed to out to file output!");
+            let _ = fo.flush();
+            assert_eq!(read_to_string(&path)
+                .expect("Failed to read the log file!"),
+                formatted.clone() + &formatter.format_log(&error_log));
+        }
+    }
+}
+
+// Test if the log file stays open between flushes until it's reopened
+#[test]
+fn log_file_handle() {
+    create_dir_all(TMP_PATH.clone()).expect("Failed to create a directory");
+    let path = TMP_PATH.to_owned() + "/log_file_handle.log";
+    let moved_path = TMP_PATH.to_owned() + "/log_file_handle.log.1";
+
+    let log = LogStruct::debug("example debug message");
+    let mut formatter = LogFormatter::default();
+    let formatted = formatter.format_log(&log);
+
+    let mut fo = FileStream::default();
+    fo.set_log_file_path(&path).expect("Failed to set the log file path!");
+    fo.enable().expect("Failed to enable file output!");
+    fo.set_max_buffer_size(Some(1));
+    fo.out(&log, &mut formatter).expect("Failed to out to file output!");
+
+    // Logs keep going to the open file after it's moved
+    std::fs::rename(&path, &moved_path).expect("Failed to move the log file!");
+    std::fs::File::create(&path).expect("Failed to create the log file!");
+    fo.out(&log, &mut formatter).expect("Failed to out to file output!");
+    assert_eq!(read_to_string(&moved_path)
+        .expect("Failed to read the moved log file!"), formatted.repeat(2));
+    assert_eq!(read_to_string(&path)
+        .expect("Failed to read the log file!"), "");
+
+    // Until the file is reopened
+    fo.reopen().expect("Failed to reopen the log file!");
+    fo.out(&log, &mut formatter).expect("Failed to out to file output!");
+    assert_eq!(read_to_string(&path)
+        .expect("Failed to read the log file!"), formatted);
+
+    // Reopening every stream at once
+    let rotate = || {
+        std::fs::rename(&path, &moved_path)
+            .expect("Failed to move the log file!");
+        std::fs::File::create(&path).expect("Failed to create the log file!");
+    };
+    rotate();
+    crate::output::reopen_all_file_streams();
+    fo.out(&log, &mut formatter).expect("Failed to out to file output!");
+    assert_eq!(read_to_string(&path)
+        .expect("Failed to read the log file!"), formatted);
+
+    // Detecting the rotation
+    fo.enable_rotation_detection();
+    rotate();
+    fo.out(&log, &mut formatter).expect("Failed to out to file output!");
+    assert_eq!(read_to_string(&path)
+        .expect("Failed to read the log file!"), formatted);
+
+    // A log file moved without a new one being created is reported
+    #[cfg(unix)]
+    {
+        std::fs::rename(&path, &moved_path)
+            .expect("Failed to move the log file!");
+        assert!(fo.out(&log, &mut formatter).is_err());
+        std::fs::File::create(&path).expect("Failed to create the log file!");
+        fo.out(&log, &mut formatter)
+            .expect("Failed to out to file output!");
+        assert_eq!(read_to_string(&path)
+            .expect("Failed to read the log file!"), formatted);
+    }
+
+    // Reopening on SIGHUP
+    #[cfg(unix)]
+    {
+        use signal_hook::{consts::SIGHUP, low_level::raise};
+
+        fo.disable_rotation_detection();
+        crate::output::install_sighup_handler()
+            .expect("Failed to install the SIGHUP handler!");
+        let generation = crate::fileio::REOPEN_GENERATION
+            .load(std::sync::atomic::Ordering::Acquire);
+        rotate();
+        raise(SIGHUP).expect("Failed to raise SIGHUP!");
+        for _ in 0..500 {
+            if crate::fileio::REOPEN_GENERATION
+                .load(std::sync::atomic::Ordering::Acquire) != generation {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        fo.out(&log, &mut formatter).expect("Failed to out to file output!");
+        assert_eq!(read_to_string(&path)
+            .expect("Failed to read the log file!"), formatted);
+    }
+
+    // Writing fails once the open file is deleted
+    fo.out(&log, &mut formatter).expect("Failed to out to file output!");
+    std::fs::remove_file(&path).expect("Failed to remove the log file!");
+    assert!(fo.out(&log, &mut formatter).is_err());
+    assert!(fo.flush().is_err());
+}
+
+// Test if writes wait for the advisory lock held by someone else
+#[test]
+fn advisory_file_lock() {
+    create_dir_all(TMP_PATH.clone()).expect("Failed to create a directory");
+    let path = TMP_PATH.to_owned() + "/advisory_file_lock.log";
+
+    let log = LogStruct::debug("example debug message");
+    let mut formatter = LogFormatter::default();
+    let formatted = formatter.format_log(&log);
+
+    let mut fo = FileStream::default();
+    fo.set_log_file_path(&path).expect("Failed to set the log file path!");
+    fo.enable().expect("Failed to enable file output!");
+    fo.set_max_buffer_size(Some(1));
+    fo.set_file_lock_mode(FileLockMode::Advisory);
+
+    // Another "process" holds the lock
+    let other = std::fs::File::open(&path)
+        .expect("Failed to open the log file!");
+    other.lock().expect("Failed to lock the log file!");
+
+    let writer = std::thread::spawn(move || {
+        fo.out(&log, &mut formatter).expect("Failed to out to file output!");
+    });
+    std::thread::sleep(Duration::from_millis(100));
+    assert_eq!(read_to_string(&path)
+        .expect("Failed to read the log file!"), "");
+
+    other.unlock().expect("Failed to unlock the log file!");
+    writer.join().expect("Failed to join the writer thread!");
+    assert_eq!(read_to_string(&path)
+        .expect("Failed to read the log file!"), formatted);
+}
+
+// Test if `FileStream::shutdown` follows the `OnDropPolicy`
+#[test]
+fn on_drop_policies() {
+    create_dir_all(TMP_PATH.clone()).expect("Failed to create a directory");
+    let path = TMP_PATH.to_owned() + "/on_drop_policies.log";
+    let fallback_path = TMP_PATH.to_owned() + "/on_drop_policies-fallback.log";
+    let _ = std::fs::remove_file(&fallback_path);
+
+    let log = LogStruct::debug("example debug message");
+    let mut formatter = LogFormatter::default();
+    let formatted = formatter.format_log(&log);
+
+    let new_stream = |policy: OnDropPolicy| {
+        let mut fo = FileStream::default();
+        fo.set_log_file_path(&path)
+            .expect("Failed to set the log file path!");
+        fo.enable().expect("Failed to enable file output!");
+        fo.set_on_drop_policy(policy);
+        fo
+    };
+    let read_log_file = |path: &str| {
+        read_to_string(path).expect("Failed to read the log file!")
+    };
+
+    // Unlocked
+    let mut fo = new_stream(OnDropPolicy::DiscardLogBuffer);
+    fo.out(&log, &mut formatter).expect("Failed to out to file output!");
+    assert_eq!(fo.shutdown().expect("Failed to shut down file output!"),
+        ShutdownOutcome::Written(1));
+    assert_eq!(read_log_file(&path), formatted);
+    assert!(!fo.is_enabled());
+    assert_eq!(fo.shutdown().expect("Failed to shut down file output!"),
+        ShutdownOutcome::Nothing);
+
+    // Locked, discarded
+    let mut fo = new_stream(OnDropPolicy::DiscardLogBuffer);
+    fo.lock_file();
+    fo.out(&log, &mut formatter).expect("Failed to out to file output!");
+    assert_eq!(fo.shutdown().expect("Failed to shut down file output!"),
+        ShutdownOutcome::Discarded(1));
+    assert_eq!(read_log_file(&path), "");
+
+    // Locked, written to the fallback file
+    let mut fo = new_stream(OnDropPolicy::FallbackFile(fallback_path.clone()));
+    fo.lock_file();
+    fo.out(&log, &mut formatter).expect("Failed to out to file output!");
+    assert_eq!(fo.shutdown().expect("Failed to shut down file output!"),
+        ShutdownOutcome::WrittenToFallback(1));
+    assert_eq!(read_log_file(&path), "");
+    assert_eq!(read_log_file(&fallback_path), formatted);
+
+    // Locked, dumped to `stderr`
+    let mut fo = new_stream(OnDropPolicy::DumpToStderr);
+    fo.lock_file();
+    fo.out(&log, &mut formatter).expect("Failed to out to file output!");
+    assert_eq!(fo.shutdown().expect("Failed to shut down file output!"),
+        ShutdownOutcome::DumpedToStderr(1));
+    assert_eq!(read_log_file(&path), "");
+
+    // Log file removed, written to the fallback file
+    let mut fo = new_stream(OnDropPolicy::FallbackFile(fallback_path.clone()));
+    fo.out(&log, &mut formatter).expect("Failed to out to file output!");
+    std::fs::remove_file(&path).expect("Failed to remove the log file!");
+    assert_eq!(fo.shutdown().expect("Failed to shut down file output!"),
+        ShutdownOutcome::WrittenToFallback(1));
+    assert_eq!(read_log_file(&fallback_path), formatted.repeat(2));
+
+    // Log file removed, nowhere to divert the logs
+    let mut fo = new_stream(OnDropPolicy::IgnoreLogFileLock);
+    fo.out(&log, &mut formatter).expect("Failed to out to file output!");
+    std::fs::remove_file(&path).expect("Failed to remove the log file!");
+    assert!(fo.shutdown().is_err());
+
+    // Written by the writer thread
+    let mut fo = new_stream(OnDropPolicy::DiscardLogBuffer);
+    fo.set_write_mode(WriteMode::Asynchronous);
+    fo.out(&log, &mut formatter).expect("Failed to out to file output!");
+    assert_eq!(fo.shutdown().expect("Failed to shut down file output!"),
+        ShutdownOutcome::Written(1));
+    assert_eq!(read_log_file(&path), formatted);
+
+    // Another process holds the lock for too long
+    let timeout = OnDropPolicy::WaitForLock { timeout_ms: 50 };
+    let mut fo = new_stream(timeout);
+    fo.lock_file();
+    fo.out(&log, &mut formatter).expect("Failed to out to file output!");
+    let other = std::fs::File::open(&path)
+        .expect("Failed to open the log file!");
+    other.lock().expect("Failed to lock the log file!");
+    assert!(fo.shutdown().is_err());
+    assert_eq!(read_log_file(&path), "");
+
+    // Another process releases the lock in time
+    let timeout = OnDropPolicy::WaitForLock { timeout_ms: 5000 };
+    let mut fo = new_stream(timeout);
+    fo.lock_file();
+    fo.out(&log, &mut formatter).expect("Failed to out to file output!");
+    let unlocker = std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(50));
+        other.unlock().expect("Failed to unlock the log file!");
+    });
+    assert_eq!(fo.shutdown().expect("Failed to shut down file output!"),
+        ShutdownOutcome::Written(1));
+    unlocker.join().expect("Failed to join the unlocking thread!");
+    assert_eq!(read_log_file(&path), formatted);
+}
+
+// Test if `Logger::flush` and `Logger::shutdown` write every buffered log
+#[test]
+fn logger_flush_and_shutdown() {
+    create_dir_all(TMP_PATH.clone()).expect("Failed to create a directory");
+    let path = TMP_PATH.to_owned() + "/logger_flush_and_shutdown.log";
+    let failover_path = TMP_PATH.to_owned() +
+        "/logger_flush_and_shutdown-failover.log";
+
+    let mut logger = Logger::default();
+    logger.output.stderr_output.disable();
+    for (fo, path) in [
+        (&mut *logger.output.file_output.lock().unwrap(), &path),
+        (&mut logger.output.failover_output.lock().unwrap().primary,
+            &failover_path),
+    ] {
+        fo.set_log_file_path(path).expect("Failed to set the log file path!");
+        fo.enable().expect("Failed to enable file output!");
+    }
+    logger.output.failover_output.lock().unwrap().enable();
+    let read_log_file = |path: &str| {
+        read_to_string(path).expect("Failed to read the log file!")
+    };
+
+    // Nothing to flush
+    logger.flush().expect("Failed to flush the logger!");
+
+    logger.info("example info message");
+    assert_eq!(read_log_file(&path), "");
+    logger.flush().expect("Failed to flush the logger!");
+    let formatted = read_log_file(&path);
+    assert!(formatted.contains("example info message"));
+    assert_eq!(read_log_file(&failover_path), formatted);
+
+    // Errors of every output are returned
+    logger.output.file_output.lock().unwrap().lock_file();
+    logger.output.failover_output.lock().unwrap().primary.lock_file();
+    logger.info("example info message");
+    let errors = logger.flush().expect_err("Flushing a locked file worked!");
+    assert_eq!(errors.len(), 2);
+
+    // Locked logs are discarded on shutdown
+    let errors = logger.shutdown()
+        .expect_err("Shutting down a locked file worked!");
+    assert_eq!(errors.len(), 2);
+    assert_eq!(read_log_file(&path), formatted);
+
+    // Shut down outputs no longer write to their log files
+    logger.info("example info message");
+    logger.shutdown().expect("Failed to shut down the logger!");
+    assert_eq!(read_log_file(&path), formatted);
+    assert!(!logger.output.file_output.lock().unwrap().is_enabled());
+}
+
+// Test if the global logger writes its buffered logs when shut down
+#[test]
+fn global_logger_shutdown() {
+    create_dir_all(TMP_PATH.clone()).expect("Failed to create a directory");
+    let path = TMP_PATH.to_owned() + "/global_logger_shutdown.log";
+
+    let enable_file_output = || {
+        let logger = LOGGER.write().unwrap();
+        let mut fo = logger.output.file_output.lock().unwrap();
+        fo.set_log_file_path(&path)
+            .expect("Failed to set the log file path!");
+        fo.enable().expect("Failed to enable file output!");
+    };
+    let read_log_file = || {
+        read_to_string(&path).expect("Failed to read the log file!")
+    };
+
+    enable_file_output();
+    info!("example info message");
+    assert_eq!(read_log_file(), "");
+    glob::shutdown().expect("Failed to shut down the global logger!");
+    assert!(read_log_file().contains("example info message"));
+
+    // With a shutdown guard
+    enable_file_output();
+    {
+        let _guard = ShutdownGuard::default();
+        info!("example info message");
+        assert_eq!(read_log_file(), "");
+    }
+    assert!(read_log_file().contains("example info message"));
+    assert!(!LOGGER.read().unwrap().output.file_output.lock().unwrap()
+        .is_enabled());
+}
@@ -0,0 +1,278 @@
+use std::{
+    collections::VecDeque,
+    sync::{
+        Arc,
+        Condvar,
+        Mutex
+    },
+    thread::{
+        Builder,
+        JoinHandle
+    },
+    time::{
+        Duration,
+        Instant
+    },
+};
+
+use crate::{
+    Error,
+    config::BackpressurePolicy,
+    fileio::{
+        LogFile,
+        WriteOptions,
+        append_with_handle
+    },
+};
+
+/// Formatted logs waiting to be written, along with the number of logs.
+type Batch = (String, usize);
+
+/// How an `AsyncWriter` queues logs and when it writes them.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub(crate) struct QueueConfig {
+    /// How many logs the queue can hold.
+    pub(crate) capacity: usize,
+    /// What to do with logs that don't fit in the queue.
+    pub(crate) backpressure_policy: BackpressurePolicy,
+    /// How long held logs may wait before being written.
+    pub(crate) flush_interval: Option<Duration>,
+    /// How many held logs are written right away.
+    pub(crate) max_held_logs: Option<usize>,
+    /// How many bytes of held logs are written right away.
+    pub(crate) max_held_bytes: Option<usize>,
+    /// Whether to reopen the log file when its path points to a new file.
+    pub(crate) detect_rotation: bool,
+    /// Whether to hold an advisory lock on the log file while writing.
+    pub(crate) lock_file: bool,
+}
+
+/// A background thread appending queued logs to a log file.
+///
+/// Logs are written as soon as they are queued, unless they are queued to be
+/// held, in which case they wait until the flush interval passes, enough of
+/// them pile up or a flush is requested.
+///
+/// Dropping the writer waits until every queued log has been written.
+pub(crate) struct AsyncWriter {
+    queue: Arc<WriterQueue>,
+    thread: Option<JoinHandle<()>>,
+}
+
+/// State shared between an `AsyncWriter` and its thread.
+struct WriterQueue {
+    state: Mutex<QueueState>,
+    /// Notified when logs are queued or the writer is shutting down.
+    queued: Condvar,
+    /// Notified when the writer has taken logs off the queue or finished
+    /// writing them.
+    written: Condvar,
+}
+
+struct QueueState {
+    config: QueueConfig,
+    batches: VecDeque<Batch>,
+    queued_logs: usize,
+    queued_bytes: usize,
+    /// Whether the next write has to be synced to the disk.
+    sync: bool,
+    /// When the oldest queued log was queued.
+    queued_since: Option<Instant>,
+    /// Whether the queue holds logs that shouldn't wait.
+    urgent: bool,
+    writing: bool,
+    shutdown: bool,
+    /// The error of the last failed write and the logs of every write that
+    /// failed since the error was last taken.
+    failed: Option<(Error, Batch)>,
+}
+
+impl AsyncWriter {
+    /// Starts a writer thread appending to the file at `path`.
+    pub(crate) fn spawn(path: &str, config: QueueConfig)
+    -> Result<AsyncWriter, Error> {
+        let queue = Arc::new(WriterQueue {
+            state: Mutex::new(QueueState {
+                config,
+                batches: VecDeque::new(),
+                queued_logs: 0,
+                queued_bytes: 0,
+                sync: false,
+                queued_since: None,
+                urgent: false,
+                writing: false,
+                shutdown: false,
+                failed: None,
+            }),
+            queued: Condvar::new(),
+            written: Condvar::new(),
+        });
+
+        let thread_queue = queue.clone();
+        let path = path.to_string();
+        match Builder::new()
+            .name(String::from("prettylogger-writer"))
+            .spawn(move || write_queued_logs(&thread_queue, &path)) {
+            Ok(thread) => Ok(AsyncWriter {
+                queue,
+                thread: Some(thread),
+            }),
+            Err(e) => Err(Error::new(&e.to_string())),
+        }
+    }
+
+    /// Queues formatted logs for writing, applying the backpressure policy
+    /// when they don't fit in the queue. With `hold` set, the logs may wait
+    /// for the flush interval before being written, and with `sync` set, the
+    /// write including them is synced to the disk.
+    ///
+    /// Returns how many logs were discarded. A batch larger than the whole
+    /// queue is accepted once the queue is empty.
+    pub(crate) fn send(&self, text: String, logs: usize, hold: bool,
+    sync: bool, config: QueueConfig) -> usize {
+        let mut state = self.queue.state.lock().unwrap();
+        let mut dropped = 0;
+        state.config = config;
+
+        let overflows = |state: &QueueState| {
+            state.queued_logs > 0 &&
+                state.queued_logs + logs > state.config.capacity
+        };
+        match config.backpressure_policy {
+            BackpressurePolicy::Block => {
+                while overflows(&state) {
+                    state = self.queue.written.wait(state).unwrap();
+                }
+            },
+            BackpressurePolicy::DropNewest => {
+                if overflows(&state) {
+                    return logs;
+                }
+            },
+            BackpressurePolicy::DropOldest => {
+                while overflows(&state) {
+                    let (oldest_text, oldest) = state.batches.pop_front()
+                        .expect("Queue is not empty");
+                    state.queued_logs -= oldest;
+                    state.queued_bytes -= oldest_text.len();
+                    dropped += oldest;
+                }
+            },
+        }
+
+        state.queued_logs += logs;
+        state.queued_bytes += text.len();
+        state.sync |= sync;
+        state.batches.push_back((text, logs));
+        state.queued_since.get_or_insert_with(Instant::now);
+        state.urgent |= !hold;
+        self.queue.queued.notify_one();
+        dropped
+    }
+
+    /// Writes every queued log without waiting for the flush interval, and
+    /// blocks until they have been written.
+    pub(crate) fn wait_until_written(&self) {
+        let mut state = self.queue.state.lock().unwrap();
+        if !state.batches.is_empty() {
+            state.urgent = true;
+            self.queue.queued.notify_one();
+        }
+        while !state.batches.is_empty() || state.writing {
+            state = self.queue.written.wait(state).unwrap();
+        }
+    }
+
+    /// Returns the error of the last failed write along with the logs that
+    /// weren't written, if any write failed since the last call.
+    pub(crate) fn take_error(&self) -> Option<(Error, Batch)> {
+        self.queue.state.lock().unwrap().failed.take()
+    }
+}
+
+/// Returns how long the queued logs may still wait before being written, or
+/// `None` if they should be written right away.
+fn hold_time(state: &QueueState) -> Option<Duration> {
+    let is_full = state.queued_logs >= state.config.capacity ||
+        state.config.max_held_logs
+            .is_some_and(|max| state.queued_logs >= max) ||
+        state.config.max_held_bytes
+            .is_some_and(|max| state.queued_bytes >= max);
+    if state.urgent || state.shutdown || is_full {
+        return None;
+    }
+
+    let interval = state.config.flush_interval?;
+    let queued_since = state.queued_since?;
+    interval.checked_sub(queued_since.elapsed())
+        .filter(|remaining| !remaining.is_zero())
+}
+
+/// Body of the writer thread. Writes everything queued in one go once it's
+/// due, and exits once shut down with an empty queue.
+fn write_queued_logs(queue: &WriterQueue, path: &str) {
+    let mut log_file: Option<LogFile> = None;
+    loop {
+        let mut state = queue.state.lock().unwrap();
+        loop {
+            if state.batches.is_empty() {
+                if state.shutdown {
+                    return;
+                }
+                state = queue.queued.wait(state).unwrap();
+                continue;
+            }
+            match hold_time(&state) {
+                Some(remaining) => {
+                    state = queue.queued.wait_timeout(state, remaining)
+                        .unwrap().0;
+                },
+                None => break,
+            }
+        }
+
+        let mut text = String::new();
+        for (batch, _) in state.batches.drain(..) {
+            text.push_str(&batch);
+        }
+        let logs = std::mem::take(&mut state.queued_logs);
+        let sync = std::mem::take(&mut state.sync);
+        state.queued_bytes = 0;
+        state.queued_since = None;
+        state.urgent = false;
+        state.writing = true;
+        let options = WriteOptions {
+            sync_data: sync,
+            detect_rotation: state.config.detect_rotation,
+            lock_file: state.config.lock_file,
+            lock_timeout: None,
+        };
+        queue.written.notify_all();
+        drop(state);
+
+        let result = append_with_handle(&mut log_file, path, &text, options);
+
+        let mut state = queue.state.lock().unwrap();
+        state.writing = false;
+        if let Err(e) = result {
+            state.failed = match state.failed.take() {
+                Some((_, (mut failed_text, failed_logs))) => {
+                    failed_text.push_str(&text);
+                    Some((e, (failed_text, failed_logs + logs)))
+                },
+                None => Some((e, (text, logs))),
+            };
+        }
+        queue.written.notify_all();
+    }
+}
+
+impl Drop for AsyncWriter {
+    fn drop(&mut self) {
+        self.queue.state.lock().unwrap().shutdown = true;
+        self.queue.queued.notify_one();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}